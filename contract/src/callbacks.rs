@@ -2,103 +2,278 @@ use std::collections::HashMap;
 
 use hodl_model::{
     draft::{DraftGroup, DraftGroupIndex, DraftIndex},
-    lockup::{Lockup, LockupClaim, LockupIndex},
+    lockup::{Lockup, LockupClaim, LockupIndex, LockupToken},
+    pause::PausableOperation,
+    storage::StoragePayment,
     util::current_timestamp_sec,
-    WrappedBalance,
+    Balance, WrappedBalance,
 };
+use near_sdk::{PromiseResult, StorageUsage};
 
 use crate::{
-    emit, ext_contract, is_promise_success, log, near_bindgen, AccountId, Contract, ContractExt, EventKind,
-    FtLockupClaimLockup, FtLockupCreateLockup, Into,
+    emit, env, event::FtLockupPayRelayerFee, ext_contract, is_promise_success, log, near_bindgen, serde_json,
+    AccountId, Contract, ContractExt, EventKind, FtLockupCreateLockup, FtTransferPromise, Into, Promise,
+    PromiseOrValue, GAS_FOR_AFTER_FT_TRANSFER,
 };
 
 #[ext_contract(ext_self)]
 pub trait SelfCallbacks {
-    fn after_ft_transfer(&mut self, account_id: AccountId, lockup_claims: Vec<LockupClaim>) -> WrappedBalance;
+    fn after_ft_transfer(
+        &mut self,
+        account_id: AccountId,
+        lockup_claims: Vec<LockupClaim>,
+        receiver_id: Option<AccountId>,
+    ) -> WrappedBalance;
 
-    fn after_lockup_termination(&mut self, account_id: AccountId, amount: WrappedBalance) -> WrappedBalance;
+    fn after_ft_transfer_call(&mut self, lockup_claims: Vec<LockupClaim>, receiver_id: AccountId) -> WrappedBalance;
+
+    fn after_claims(&mut self) -> WrappedBalance;
+
+    fn after_claim_for(
+        &mut self,
+        account_id: AccountId,
+        lockup_claims: Vec<LockupClaim>,
+        relayer_id: AccountId,
+        fee: WrappedBalance,
+        token: Option<LockupToken>,
+    ) -> PromiseOrValue<WrappedBalance>;
+
+    fn after_relayer_fee_transfer(
+        &mut self,
+        account_id: AccountId,
+        fee_claims: Vec<LockupClaim>,
+        relayer_id: AccountId,
+        claimed_balance: WrappedBalance,
+    ) -> WrappedBalance;
+
+    fn after_lockup_termination(
+        &mut self,
+        account_id: AccountId,
+        amount: WrappedBalance,
+        token: Option<LockupToken>,
+        payer_id: Option<AccountId>,
+        reserved_bytes: Option<StorageUsage>,
+    ) -> WrappedBalance;
+
+    fn after_auto_claims(&mut self, next_index: LockupIndex) -> LockupIndex;
 
     fn convert_drafts(&mut self, draft_ids: Vec<DraftIndex>) -> Vec<LockupIndex>;
 }
 
 #[near_bindgen]
 impl SelfCallbacks for Contract {
+    /// Handles claim transfers of any asset, native ones included.
     #[private]
-    fn after_ft_transfer(&mut self, account_id: AccountId, lockup_claims: Vec<LockupClaim>) -> WrappedBalance {
-        let promise_success = is_promise_success();
-        let mut total_balance = 0;
-        if promise_success {
-            let mut remove_indices = vec![];
-            let mut events: Vec<FtLockupClaimLockup> = vec![];
-            for LockupClaim {
-                index,
-                is_final,
-                claim_amount,
-            } in lockup_claims
-            {
-                if is_final {
-                    remove_indices.push(index);
-                }
-                total_balance += claim_amount.0;
-                let event = FtLockupClaimLockup {
-                    id: index,
-                    amount: claim_amount,
-                };
-                events.push(event);
-            }
-            if !remove_indices.is_empty() {
-                let mut indices = self.account_lockups.get(&account_id).unwrap_or_default();
-                for index in remove_indices {
-                    indices.remove(&index);
-                }
-                self.internal_save_account_lockups(&account_id, indices);
-            }
-            emit(EventKind::FtLockupClaimLockup(events));
+    fn after_ft_transfer(
+        &mut self,
+        account_id: AccountId,
+        lockup_claims: Vec<LockupClaim>,
+        receiver_id: Option<AccountId>,
+    ) -> WrappedBalance {
+        // callbacks scheduled before claims to a receiver were introduced don't have it
+        let receiver_id = receiver_id.unwrap_or(account_id);
+        if is_promise_success() {
+            self.internal_finalize_claims(lockup_claims, &receiver_id).into()
         } else {
-            log!("Token transfer has failed. Refunding.");
-            let mut modified = false;
-            let mut indices = self.account_lockups.get(&account_id).unwrap_or_default();
-            for LockupClaim {
-                index, claim_amount, ..
-            } in lockup_claims
-            {
-                if indices.insert(index) {
-                    modified = true;
-                }
-                let mut lockup = self.lockups.get(u64::from(index)).unwrap();
-                lockup.claimed_balance -= claim_amount.0;
-                self.lockups.replace(u64::from(index), &lockup);
-            }
+            log!("Token transfer to {} has failed. Refunding.", receiver_id);
+            self.internal_rollback_claims(lockup_claims);
+            0.into()
+        }
+    }
+
+    /// Sums up the amounts claimed by the joined transfers of several tokens, a failed transfer claims nothing.
+    #[private]
+    fn after_claims(&mut self) -> WrappedBalance {
+        (0..env::promise_results_count())
+            .filter_map(|index| match env::promise_result(index) {
+                PromiseResult::Successful(value) => serde_json::from_slice::<WrappedBalance>(&value).ok(),
+                PromiseResult::Failed => None,
+            })
+            .map(|claimed_balance| claimed_balance.0)
+            .sum::<Balance>()
+            .into()
+    }
+
+    #[private]
+    fn after_ft_transfer_call(&mut self, lockup_claims: Vec<LockupClaim>, receiver_id: AccountId) -> WrappedBalance {
+        let total_balance: Balance = lockup_claims.iter().map(|claim| claim.claim_amount.0).sum();
+        // The token refunds the whole amount if the transfer call fails
+        let used_balance = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<WrappedBalance>(&value)
+                .map_or(0, |used_balance| used_balance.0.min(total_balance)),
+            PromiseResult::Failed => 0,
+        };
+        if used_balance < total_balance {
+            log!(
+                "Receiver {} returned {} tokens. Refunding.",
+                receiver_id,
+                total_balance - used_balance
+            );
+        }
 
-            if modified {
-                self.internal_save_account_lockups(&account_id, indices);
+        // Returning the unused balance to the last claimed lockups
+        let mut unused_balance = total_balance - used_balance;
+        let mut used_claims = vec![];
+        let mut unused_claims = vec![];
+        for LockupClaim {
+            index,
+            claim_amount,
+            is_final,
+        } in lockup_claims.into_iter().rev()
+        {
+            let unused_amount = unused_balance.min(claim_amount.0);
+            unused_balance -= unused_amount;
+            if unused_amount > 0 {
+                unused_claims.push(LockupClaim {
+                    index,
+                    claim_amount: unused_amount.into(),
+                    is_final: false,
+                });
+            }
+            if claim_amount.0 > unused_amount {
+                used_claims.push(LockupClaim {
+                    index,
+                    claim_amount: (claim_amount.0 - unused_amount).into(),
+                    is_final: is_final && unused_amount == 0,
+                });
             }
         }
-        total_balance.into()
+        self.internal_rollback_claims(unused_claims);
+
+        self.internal_finalize_claims(used_claims, &receiver_id).into()
+    }
+
+    #[private]
+    fn after_claim_for(
+        &mut self,
+        account_id: AccountId,
+        lockup_claims: Vec<LockupClaim>,
+        relayer_id: AccountId,
+        fee: WrappedBalance,
+        token: Option<LockupToken>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        if !is_promise_success() {
+            log!("Token transfer to {} has failed. Refunding.", account_id);
+            self.internal_rollback_claims(lockup_claims);
+            return PromiseOrValue::Value(0.into());
+        }
+
+        let (lockup_claims, fee_claims) = split_off_claims(lockup_claims, fee.0);
+        let claimed_balance = self.internal_finalize_claims(lockup_claims, &account_id);
+        if fee_claims.is_empty() {
+            return PromiseOrValue::Value(claimed_balance.into());
+        }
+
+        Contract::internal_transfer(
+            self.internal_lockup_token(token.as_ref()),
+            &relayer_id,
+            fee.0,
+            Some("Relayer fee".to_string()),
+        )
+        .then(
+            ext_self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                .after_relayer_fee_transfer(account_id, fee_claims, relayer_id, claimed_balance.into()),
+        )
+        .into()
+    }
+
+    /// Completes the claims of the relayer fee, the fee returns to the lockups if the transfer has failed.
+    /// Returns the total claimed amount.
+    #[private]
+    fn after_relayer_fee_transfer(
+        &mut self,
+        account_id: AccountId,
+        fee_claims: Vec<LockupClaim>,
+        relayer_id: AccountId,
+        claimed_balance: WrappedBalance,
+    ) -> WrappedBalance {
+        if !is_promise_success() {
+            log!("Fee transfer to {} has failed. Refunding.", relayer_id);
+            self.internal_rollback_claims(fee_claims);
+            return claimed_balance;
+        }
+
+        let fee = self.internal_finalize_claims(fee_claims, &relayer_id);
+        emit(EventKind::FtLockupPayRelayerFee(vec![FtLockupPayRelayerFee {
+            account_id,
+            relayer_id,
+            amount: fee.into(),
+        }]));
+        (claimed_balance.0 + fee).into()
     }
 
     #[private]
-    fn after_lockup_termination(&mut self, account_id: AccountId, amount: WrappedBalance) -> WrappedBalance {
+    fn after_lockup_termination(
+        &mut self,
+        account_id: AccountId,
+        amount: WrappedBalance,
+        token: Option<LockupToken>,
+        payer_id: Option<AccountId>,
+        reserved_bytes: Option<StorageUsage>,
+    ) -> WrappedBalance {
+        // callbacks scheduled before the update that introduced the reserve don't have it
+        let reserved_bytes = reserved_bytes.unwrap_or(0);
         if is_promise_success() {
+            if let Some(payer_id) = payer_id {
+                self.internal_release_storage(&payer_id, reserved_bytes);
+            }
             return amount;
         }
 
         log!("Lockup termination transfer has failed.");
-        // There is no internal balance, so instead we create a new lockup.
-        let lockup = Lockup::new_unlocked_since(account_id, amount.0, current_timestamp_sec());
+        // There is no internal balance, so instead we create a new lockup paid with the reserved storage.
+        let mut lockup = Lockup::new_unlocked_since(account_id, amount.0, current_timestamp_sec());
+        lockup.token = token;
+        let payer_id = payer_id.filter(|_| reserved_bytes > 0);
+        if let Some(migration) = self.state_migration.as_mut() {
+            // the new lockups get their indices after the old ones are moved
+            migration.defer_lockup(lockup);
+            if let Some(payer_id) = payer_id {
+                self.internal_release_storage(&payer_id, reserved_bytes);
+            }
+            return 0.into();
+        }
+        let initial_storage_usage = env::storage_usage();
+        // the size of the payment doesn't depend on the paid bytes, so it's stored before they are known
+        lockup.storage_payment = payer_id.clone().map(|payer_id| StoragePayment { payer_id, bytes: 0 });
         let lockup_index = self.internal_add_lockup(&lockup);
+        if let Some(payer_id) = payer_id {
+            // the callback can't fail, so the contract covers the storage above the reserved one
+            let paid_bytes = (env::storage_usage() - initial_storage_usage).min(reserved_bytes);
+            lockup.storage_payment = Some(StoragePayment {
+                payer_id: payer_id.clone(),
+                bytes: paid_bytes,
+            });
+            self.lockups.replace(u64::from(lockup_index), &lockup);
+            self.internal_release_storage(&payer_id, reserved_bytes - paid_bytes);
+        }
         let event: FtLockupCreateLockup = (lockup_index, lockup, None).into();
         emit(EventKind::FtLockupCreateLockup(vec![event]));
         0.into()
     }
 
+    /// Passes the index to continue auto-claims from, the transfers are completed by their own callbacks.
+    #[private]
+    fn after_auto_claims(&mut self, next_index: LockupIndex) -> LockupIndex {
+        next_index
+    }
+
     fn convert_drafts(&mut self, draft_ids: Vec<DraftIndex>) -> Vec<LockupIndex> {
+        self.assert_not_paused(PausableOperation::ConvertDrafts);
+        self.assert_state_migrated();
         let mut draft_group_lookup: HashMap<DraftGroupIndex, DraftGroup> = HashMap::new();
         let mut events: Vec<FtLockupCreateLockup> = vec![];
         let lockup_ids: Vec<LockupIndex> = draft_ids
             .iter()
             .map(|draft_id| {
-                let draft = self.drafts.remove(draft_id as _).expect("draft not found");
+                let initial_storage_usage = env::storage_usage();
+                let mut draft = self.drafts.remove(draft_id as _).expect("draft not found");
+                // the storage of the lockup is paid by the creator of the draft
+                let storage_payer_id = draft.storage_payment.as_mut().map(|payment| {
+                    self.internal_refund_storage(payment, initial_storage_usage);
+                    payment.payer_id.clone()
+                });
                 let draft_group = draft_group_lookup.entry(draft.draft_group_id).or_insert_with(|| {
                     self.draft_groups
                         .get(&draft.draft_group_id as _)
@@ -112,8 +287,12 @@ impl SelfCallbacks for Contract {
                 assert!(draft_group.total_amount >= amount, "Invariant");
                 draft_group.total_amount -= amount;
 
-                let lockup = draft.lockup_create.into_lockup(payer_id);
-                let index = self.internal_add_lockup(&lockup);
+                let mut lockup = draft.lockup_create.into_lockup(payer_id);
+                lockup.token = draft_group.token_account_id.clone().map(LockupToken::Ft);
+                let index = match storage_payer_id {
+                    Some(storage_payer_id) => self.internal_add_paid_lockup(&mut lockup, &storage_payer_id),
+                    None => self.internal_add_lockup(&lockup),
+                };
 
                 let event: FtLockupCreateLockup = (index, lockup, Some(*draft_id)).into();
                 events.push(event);
@@ -135,3 +314,37 @@ impl SelfCallbacks for Contract {
         lockup_ids
     }
 }
+
+/// Splits `amount` off the end of the claims, so it can be transferred separately.
+/// A lockup claim that is split is final only in its part of `amount`, which is completed last.
+fn split_off_claims(lockup_claims: Vec<LockupClaim>, amount: Balance) -> (Vec<LockupClaim>, Vec<LockupClaim>) {
+    let mut amount = amount;
+    let mut claims = vec![];
+    let mut split_claims = vec![];
+    for LockupClaim {
+        index,
+        claim_amount,
+        is_final,
+    } in lockup_claims.into_iter().rev()
+    {
+        let split_off_amount = amount.min(claim_amount.0);
+        amount -= split_off_amount;
+        if split_off_amount > 0 {
+            split_claims.push(LockupClaim {
+                index,
+                claim_amount: split_off_amount.into(),
+                is_final,
+            });
+        }
+        if claim_amount.0 > split_off_amount {
+            claims.push(LockupClaim {
+                index,
+                claim_amount: (claim_amount.0 - split_off_amount).into(),
+                is_final: is_final && split_off_amount == 0,
+            });
+        }
+    }
+    assert_eq!(amount, 0, "Invariant");
+
+    (claims, split_claims)
+}
@@ -1,10 +1,12 @@
 use hodl_model::{
     draft::{Draft, DraftGroupIndex, DraftIndex},
-    lockup::{Lockup, LockupIndex},
-    TimestampSec, WrappedBalance,
+    lockup::{Lockup, LockupIndex, LockupToken},
+    pause::PausableOperation,
+    role::Role,
+    TimestampSec, TokenAccountId, WrappedBalance,
 };
 
-use crate::{log, serde_json, AccountId, Into, Serialize, PACKAGE_NAME, VERSION};
+use crate::{log, serde_json, AccountId, Base58CryptoHash, Into, Serialize, PACKAGE_NAME, VERSION};
 
 /// Events to be generated by the contract according to NEP-297
 
@@ -12,6 +14,40 @@ use crate::{log, serde_json, AccountId, Into, Serialize, PACKAGE_NAME, VERSION};
 #[serde(crate = "near_sdk::serde")]
 pub struct FtLockupUpdateContract {}
 
+/// The manager proposed another account to take over, `None` if the proposal was withdrawn.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupProposeManager {
+    pub manager: Option<AccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupAcceptManager {
+    pub manager: AccountId,
+    pub old_manager: AccountId,
+}
+
+/// The code with `code_hash` can be deployed since `activation_timestamp`.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupStageUpdate {
+    pub code_hash: Base58CryptoHash,
+    pub activation_timestamp: TimestampSec,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupCancelUpdate {
+    pub code_hash: Base58CryptoHash,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupSetUpdateDelay {
+    pub update_delay: TimestampSec,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct FtLockupNew {
@@ -42,6 +78,56 @@ pub struct FtLockupRemoveFromDraftOperatorsWhitelist {
     pub account_ids: Vec<AccountId>,
 }
 
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupAddToRelayersWhitelist {
+    pub account_ids: Vec<AccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupRemoveFromRelayersWhitelist {
+    pub account_ids: Vec<AccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupAddToTokenWhitelist {
+    pub token_account_ids: Vec<TokenAccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupRemoveFromTokenWhitelist {
+    pub token_account_ids: Vec<TokenAccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupGrantRole {
+    pub role: Role,
+    pub account_ids: Vec<AccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupRevokeRole {
+    pub role: Role,
+    pub account_ids: Vec<AccountId>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupPauseOperations {
+    pub operations: Vec<PausableOperation>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupUnpauseOperations {
+    pub operations: Vec<PausableOperation>,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct FtLockupCreateLockup {
@@ -52,6 +138,9 @@ pub struct FtLockupCreateLockup {
     pub finish: TimestampSec,
     pub terminatable: bool,
     pub draft_id: Option<DraftIndex>,
+    /// Omitted for lockups of the default token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<LockupToken>,
 }
 
 impl From<(LockupIndex, Lockup, Option<DraftIndex>)> for FtLockupCreateLockup {
@@ -61,10 +150,11 @@ impl From<(LockupIndex, Lockup, Option<DraftIndex>)> for FtLockupCreateLockup {
             id,
             account_id: lockup.account_id,
             balance: lockup.schedule.total_balance().into(),
-            start: lockup.schedule.0.first().unwrap().timestamp,
-            finish: lockup.schedule.0.last().unwrap().timestamp,
+            start: lockup.schedule.start_timestamp(),
+            finish: lockup.schedule.finish_timestamp(),
             terminatable: lockup.termination_config.is_some(),
             draft_id,
+            token: lockup.token,
         }
     }
 }
@@ -74,6 +164,17 @@ impl From<(LockupIndex, Lockup, Option<DraftIndex>)> for FtLockupCreateLockup {
 pub struct FtLockupClaimLockup {
     pub id: LockupIndex,
     pub amount: WrappedBalance,
+    /// The account that received the claimed tokens
+    pub receiver_id: AccountId,
+}
+
+/// A relayer took a fee out of the tokens it claimed on behalf of the lockup owner.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupPayRelayerFee {
+    pub account_id: AccountId,
+    pub relayer_id: AccountId,
+    pub amount: WrappedBalance,
 }
 
 #[derive(Serialize, Debug)]
@@ -84,6 +185,117 @@ pub struct FtLockupTerminateLockup {
     pub unvested_balance: WrappedBalance,
 }
 
+/// A part of the unvested balance was taken back, the rest keeps vesting.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupReduceLockup {
+    pub id: LockupIndex,
+    pub timestamp: TimestampSec,
+    pub reduced_balance: WrappedBalance,
+}
+
+/// A termination was announced, it's executed with `FtLockupTerminateLockup` after `executable_at`.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupScheduleTermination {
+    pub id: LockupIndex,
+    pub termination_timestamp: TimestampSec,
+    pub executable_at: TimestampSec,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupCancelTermination {
+    pub id: LockupIndex,
+}
+
+/// The unlocking and the vesting of the lockup are frozen until it's resumed.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupPauseLockup {
+    pub id: LockupIndex,
+    pub timestamp: TimestampSec,
+}
+
+/// The lockup is unfrozen, its schedules after the pause are delayed by `pause_duration`.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupResumeLockup {
+    pub id: LockupIndex,
+    pub timestamp: TimestampSec,
+    pub pause_duration: TimestampSec,
+}
+
+/// The finished lockup is replaced with its final state, see `Lockup::to_tombstone`.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupPruneLockup {
+    pub id: LockupIndex,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupSetTerminationGracePeriod {
+    pub grace_period: TimestampSec,
+}
+
+/// The maximum fee relayers can take, in basis points of the claimed amount.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupSetMaxRelayerFee {
+    pub max_fee_bps: u16,
+}
+
+/// The number of the latest claims kept per lockup, zero stands for no history.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupSetClaimHistoryLimit {
+    pub limit: u32,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupTransferLockup {
+    pub id: LockupIndex,
+    pub old_account_id: AccountId,
+    pub new_account_id: AccountId,
+}
+
+/// The owner asked to move a terminable lockup, the transfer waits for the approval of the lockup terminator.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupRequestLockupTransfer {
+    pub id: LockupIndex,
+    pub account_id: AccountId,
+    pub receiver_id: AccountId,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupCancelLockupTransfer {
+    pub id: LockupIndex,
+    pub receiver_id: AccountId,
+}
+
+/// A part of the lockup balance was moved to new lockups, which are reported by `FtLockupCreateLockup`.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupSplitLockup {
+    pub id: LockupIndex,
+    /// The remaining balance of the lockup
+    pub balance: WrappedBalance,
+    pub new_lockup_ids: Vec<LockupIndex>,
+}
+
+/// The lockups were merged into a new lockup, which is reported by `FtLockupCreateLockup`.
+/// The merged lockups keep only their claimed balance and are pruned.
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtLockupMergeLockups {
+    pub id: LockupIndex,
+    pub merged_lockup_ids: Vec<LockupIndex>,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct FtLockupCreateDraftGroup {
@@ -110,8 +322,8 @@ impl From<(DraftIndex, Draft)> for FtLockupCreateDraft {
             draft_group_id: draft.draft_group_id,
             account_id: draft.lockup_create.account_id,
             balance: draft.lockup_create.schedule.total_balance().into(),
-            start: draft.lockup_create.schedule.0.first().unwrap().timestamp,
-            finish: draft.lockup_create.schedule.0.last().unwrap().timestamp,
+            start: draft.lockup_create.schedule.start_timestamp(),
+            finish: draft.lockup_create.schedule.finish_timestamp(),
             terminatable: draft.lockup_create.vesting_schedule.is_some(),
         }
     }
@@ -147,15 +359,43 @@ pub(crate) enum EventKind {
     FtLockupRemoveFromDepositWhitelist(FtLockupRemoveFromDepositWhitelist),
     FtLockupAddToDraftOperatorsWhitelist(FtLockupAddToDraftOperatorsWhitelist),
     FtLockupRemoveFromDraftOperatorsWhitelist(FtLockupRemoveFromDraftOperatorsWhitelist),
+    FtLockupAddToRelayersWhitelist(FtLockupAddToRelayersWhitelist),
+    FtLockupRemoveFromRelayersWhitelist(FtLockupRemoveFromRelayersWhitelist),
+    FtLockupAddToTokenWhitelist(FtLockupAddToTokenWhitelist),
+    FtLockupRemoveFromTokenWhitelist(FtLockupRemoveFromTokenWhitelist),
+    FtLockupGrantRole(FtLockupGrantRole),
+    FtLockupRevokeRole(FtLockupRevokeRole),
+    FtLockupPauseOperations(FtLockupPauseOperations),
+    FtLockupUnpauseOperations(FtLockupUnpauseOperations),
     FtLockupCreateLockup(Vec<FtLockupCreateLockup>),
     FtLockupClaimLockup(Vec<FtLockupClaimLockup>),
+    FtLockupPayRelayerFee(Vec<FtLockupPayRelayerFee>),
     FtLockupTerminateLockup(Vec<FtLockupTerminateLockup>),
+    FtLockupReduceLockup(Vec<FtLockupReduceLockup>),
+    FtLockupScheduleTermination(Vec<FtLockupScheduleTermination>),
+    FtLockupCancelTermination(Vec<FtLockupCancelTermination>),
+    FtLockupPauseLockup(Vec<FtLockupPauseLockup>),
+    FtLockupResumeLockup(Vec<FtLockupResumeLockup>),
+    FtLockupPruneLockup(Vec<FtLockupPruneLockup>),
+    FtLockupSetTerminationGracePeriod(FtLockupSetTerminationGracePeriod),
+    FtLockupSetClaimHistoryLimit(FtLockupSetClaimHistoryLimit),
+    FtLockupSetMaxRelayerFee(FtLockupSetMaxRelayerFee),
+    FtLockupTransferLockup(Vec<FtLockupTransferLockup>),
+    FtLockupRequestLockupTransfer(Vec<FtLockupRequestLockupTransfer>),
+    FtLockupCancelLockupTransfer(Vec<FtLockupCancelLockupTransfer>),
+    FtLockupSplitLockup(Vec<FtLockupSplitLockup>),
+    FtLockupMergeLockups(Vec<FtLockupMergeLockups>),
     FtLockupCreateDraftGroup(Vec<FtLockupCreateDraftGroup>),
     FtLockupCreateDraft(Vec<FtLockupCreateDraft>),
     FtLockupFundDraftGroup(Vec<FtLockupFundDraftGroup>),
     FtLockupDiscardDraftGroup(Vec<FtLockupDiscardDraftGroup>),
     FtLockupDeleteDraft(Vec<FtLockupDeleteDraft>),
     FtLockupUpdateContract(FtLockupUpdateContract),
+    FtLockupProposeManager(FtLockupProposeManager),
+    FtLockupAcceptManager(FtLockupAcceptManager),
+    FtLockupStageUpdate(FtLockupStageUpdate),
+    FtLockupCancelUpdate(FtLockupCancelUpdate),
+    FtLockupSetUpdateDelay(FtLockupSetUpdateDelay),
 }
 
 #[derive(Serialize, Debug)]
@@ -328,168 +568,660 @@ mod tests {
     }
 
     #[test]
-    fn test_ft_lockup_create_lockup() {
+    fn test_ft_lockup_add_to_relayers_whitelist() {
         testing_env!(get_context());
 
-        let account_id = AccountId::from_str("alice.near").unwrap();
-        let balance: WrappedBalance = 10_000.into();
-        let timestamp: TimestampSec = 1_500_000_000;
-        let lockup = Lockup::new_unlocked_since(account_id.clone(), balance.0, timestamp);
-        let lockup_id: LockupIndex = 100;
-        let draft_id: DraftIndex = 33;
-
-        let event: FtLockupCreateLockup = (100, lockup, Some(draft_id)).into();
-
-        emit(EventKind::FtLockupCreateLockup(vec![event]));
+        let account_ids: Vec<AccountId> = vec!["alice.near", "bob.near"]
+            .iter()
+            .map(|s| AccountId::from_str(s).unwrap())
+            .collect();
+        emit(EventKind::FtLockupAddToRelayersWhitelist(
+            FtLockupAddToRelayersWhitelist { account_ids },
+        ));
         compare_json(
             &test_utils::get_logs()[0],
             json!({
                 "standard": PACKAGE_NAME,
                 "version": VERSION,
-                "event": "ft_lockup_create_lockup",
-                "data": [
-                    {
-                        "id": lockup_id,
-                        "account_id": account_id,
-                        "balance": balance,
-                        "start": timestamp - 1,
-                        "finish": timestamp,
-                        "terminatable": false,
-                        "draft_id": Some(draft_id),
-                    },
-                ],
+                "event": "ft_lockup_add_to_relayers_whitelist",
+                "data": { "account_ids": ["alice.near", "bob.near"] },
             }),
         );
     }
 
     #[test]
-    fn test_ft_lockup_claim_lockup() {
+    fn test_ft_lockup_remove_from_relayers_whitelist() {
         testing_env!(get_context());
 
-        let lockup_id: LockupIndex = 100;
-        let amount: WrappedBalance = 10000.into();
-
-        let event = FtLockupClaimLockup { id: lockup_id, amount };
-
-        emit(EventKind::FtLockupClaimLockup(vec![event]));
+        let account_ids: Vec<AccountId> = vec!["alice.near", "bob.near"]
+            .iter()
+            .map(|s| AccountId::from_str(s).unwrap())
+            .collect();
+        emit(EventKind::FtLockupRemoveFromRelayersWhitelist(
+            FtLockupRemoveFromRelayersWhitelist { account_ids },
+        ));
         compare_json(
             &test_utils::get_logs()[0],
             json!({
                 "standard": PACKAGE_NAME,
                 "version": VERSION,
-                "event": "ft_lockup_claim_lockup",
-                "data": [
-                    {
-                        "id": lockup_id,
-                        "amount": amount,
-                    },
-                ],
+                "event": "ft_lockup_remove_from_relayers_whitelist",
+                "data": { "account_ids": ["alice.near", "bob.near"] },
             }),
         );
     }
 
     #[test]
-    fn test_ft_lockup_terminate_lockup() {
+    fn test_ft_lockup_add_to_token_whitelist() {
         testing_env!(get_context());
 
-        let lockup_id: LockupIndex = 100;
-        let termination_timestamp: TimestampSec = 1_800_000_000;
-        let unvested_balance: WrappedBalance = 10000.into();
-
-        let event = FtLockupTerminateLockup {
-            id: lockup_id,
-            termination_timestamp,
-            unvested_balance,
-        };
-
-        emit(EventKind::FtLockupTerminateLockup(vec![event]));
+        let token_account_ids: Vec<AccountId> = vec!["usdt.near", "wrap.near"]
+            .iter()
+            .map(|s| AccountId::from_str(s).unwrap())
+            .collect();
+        emit(EventKind::FtLockupAddToTokenWhitelist(FtLockupAddToTokenWhitelist {
+            token_account_ids,
+        }));
         compare_json(
             &test_utils::get_logs()[0],
             json!({
                 "standard": PACKAGE_NAME,
                 "version": VERSION,
-                "event": "ft_lockup_terminate_lockup",
-                "data": [
-                    {
-                        "id": lockup_id,
-                        "termination_timestamp": termination_timestamp,
-                        "unvested_balance": unvested_balance,
-                    },
-                ],
+                "event": "ft_lockup_add_to_token_whitelist",
+                "data": { "token_account_ids": ["usdt.near", "wrap.near"] },
             }),
         );
     }
 
     #[test]
-    fn test_ft_lockup_create_draft_group() {
+    fn test_ft_lockup_remove_from_token_whitelist() {
         testing_env!(get_context());
 
-        let draft_group_id: DraftGroupIndex = 22;
-
-        let event = FtLockupCreateDraftGroup { id: draft_group_id };
-
-        emit(EventKind::FtLockupCreateDraftGroup(vec![event]));
+        let token_account_ids: Vec<AccountId> = vec!["usdt.near", "wrap.near"]
+            .iter()
+            .map(|s| AccountId::from_str(s).unwrap())
+            .collect();
+        emit(EventKind::FtLockupRemoveFromTokenWhitelist(
+            FtLockupRemoveFromTokenWhitelist { token_account_ids },
+        ));
         compare_json(
             &test_utils::get_logs()[0],
             json!({
                 "standard": PACKAGE_NAME,
                 "version": VERSION,
-                "event": "ft_lockup_create_draft_group",
-                "data": [
-                    {
-                        "id": draft_group_id,
-                    },
-                ],
+                "event": "ft_lockup_remove_from_token_whitelist",
+                "data": { "token_account_ids": ["usdt.near", "wrap.near"] },
             }),
         );
     }
 
     #[test]
-    fn test_ft_lockup_create_draft() {
+    fn test_ft_lockup_grant_role() {
         testing_env!(get_context());
 
-        let account_id = AccountId::from_str("alice.near").unwrap();
-        let balance: WrappedBalance = 10_000.into();
-        let timestamp: TimestampSec = 1_500_000_000;
-        let lockup_create = LockupCreate {
-            account_id: account_id.clone(),
-            schedule: Schedule::new_unlocked_since(balance.0, timestamp),
-            vesting_schedule: None,
-        };
-        let draft_group_id: DraftGroupIndex = 123;
-        let draft = Draft {
-            draft_group_id,
-            lockup_create,
-        };
-        let draft_id: DraftIndex = 33;
-
-        let event: FtLockupCreateDraft = (draft_id, draft).into();
-
-        emit(EventKind::FtLockupCreateDraft(vec![event]));
-
+        let account_ids: Vec<AccountId> = vec!["alice.near", "bob.near"]
+            .iter()
+            .map(|s| AccountId::from_str(s).unwrap())
+            .collect();
+        emit(EventKind::FtLockupGrantRole(FtLockupGrantRole {
+            role: Role::LockupCreator,
+            account_ids,
+        }));
         compare_json(
             &test_utils::get_logs()[0],
             json!({
                 "standard": PACKAGE_NAME,
                 "version": VERSION,
-                "event": "ft_lockup_create_draft",
-                "data": [
-                    {
-                        "id": draft_id,
-                        "draft_group_id": draft_group_id,
-                        "account_id": account_id.to_string(),
-                        "balance": balance,
-                        "start": timestamp - 1,
-                        "finish": timestamp,
-                        "terminatable": false,
-                    },
-                ],
+                "event": "ft_lockup_grant_role",
+                "data": { "role": "lockup_creator", "account_ids": ["alice.near", "bob.near"] },
             }),
         );
     }
 
     #[test]
-    fn test_ft_lockup_fund_draft_group() {
+    fn test_ft_lockup_revoke_role() {
+        testing_env!(get_context());
+
+        let account_ids: Vec<AccountId> = vec!["alice.near"]
+            .iter()
+            .map(|s| AccountId::from_str(s).unwrap())
+            .collect();
+        emit(EventKind::FtLockupRevokeRole(FtLockupRevokeRole {
+            role: Role::Terminator,
+            account_ids,
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_revoke_role",
+                "data": { "role": "terminator", "account_ids": ["alice.near"] },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_pause_operations() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupPauseOperations(FtLockupPauseOperations {
+            operations: vec![PausableOperation::Claim, PausableOperation::ConvertDrafts],
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_pause_operations",
+                "data": { "operations": ["claim", "convert_drafts"] },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_unpause_operations() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupUnpauseOperations(FtLockupUnpauseOperations {
+            operations: vec![PausableOperation::CreateLockup],
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_unpause_operations",
+                "data": { "operations": ["create_lockup"] },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_create_lockup() {
+        testing_env!(get_context());
+
+        let account_id = AccountId::from_str("alice.near").unwrap();
+        let balance: WrappedBalance = 10_000.into();
+        let timestamp: TimestampSec = 1_500_000_000;
+        let lockup = Lockup::new_unlocked_since(account_id.clone(), balance.0, timestamp);
+        let lockup_id: LockupIndex = 100;
+        let draft_id: DraftIndex = 33;
+
+        let event: FtLockupCreateLockup = (100, lockup, Some(draft_id)).into();
+
+        emit(EventKind::FtLockupCreateLockup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_create_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "account_id": account_id,
+                        "balance": balance,
+                        "start": timestamp - 1,
+                        "finish": timestamp,
+                        "terminatable": false,
+                        "draft_id": Some(draft_id),
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_claim_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let amount: WrappedBalance = 10000.into();
+        let receiver_id = AccountId::from_str("exchange.near").unwrap();
+
+        let event = FtLockupClaimLockup {
+            id: lockup_id,
+            amount,
+            receiver_id,
+        };
+
+        emit(EventKind::FtLockupClaimLockup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_claim_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "amount": amount,
+                        "receiver_id": "exchange.near",
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_pay_relayer_fee() {
+        testing_env!(get_context());
+
+        let amount: WrappedBalance = 100.into();
+
+        let event = FtLockupPayRelayerFee {
+            account_id: AccountId::from_str("alice.near").unwrap(),
+            relayer_id: AccountId::from_str("relayer.near").unwrap(),
+            amount,
+        };
+
+        emit(EventKind::FtLockupPayRelayerFee(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_pay_relayer_fee",
+                "data": [
+                    {
+                        "account_id": "alice.near",
+                        "relayer_id": "relayer.near",
+                        "amount": amount,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_terminate_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let termination_timestamp: TimestampSec = 1_800_000_000;
+        let unvested_balance: WrappedBalance = 10000.into();
+
+        let event = FtLockupTerminateLockup {
+            id: lockup_id,
+            termination_timestamp,
+            unvested_balance,
+        };
+
+        emit(EventKind::FtLockupTerminateLockup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_terminate_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "termination_timestamp": termination_timestamp,
+                        "unvested_balance": unvested_balance,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_reduce_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let timestamp: TimestampSec = 1_800_000_000;
+        let reduced_balance: WrappedBalance = 2500.into();
+
+        let event = FtLockupReduceLockup {
+            id: lockup_id,
+            timestamp,
+            reduced_balance,
+        };
+
+        emit(EventKind::FtLockupReduceLockup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_reduce_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "timestamp": timestamp,
+                        "reduced_balance": reduced_balance,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_schedule_termination() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let termination_timestamp: TimestampSec = 1_800_000_000;
+        let executable_at: TimestampSec = 1_800_604_800;
+
+        let event = FtLockupScheduleTermination {
+            id: lockup_id,
+            termination_timestamp,
+            executable_at,
+        };
+
+        emit(EventKind::FtLockupScheduleTermination(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_schedule_termination",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "termination_timestamp": termination_timestamp,
+                        "executable_at": executable_at,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_cancel_termination() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+
+        emit(EventKind::FtLockupCancelTermination(vec![FtLockupCancelTermination {
+            id: lockup_id,
+        }]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_cancel_termination",
+                "data": [{ "id": lockup_id }],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_pause_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let timestamp: TimestampSec = 1_800_000_000;
+
+        emit(EventKind::FtLockupPauseLockup(vec![FtLockupPauseLockup {
+            id: lockup_id,
+            timestamp,
+        }]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_pause_lockup",
+                "data": [{ "id": lockup_id, "timestamp": timestamp }],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_resume_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let timestamp: TimestampSec = 1_800_000_000;
+        let pause_duration: TimestampSec = 2_592_000;
+
+        emit(EventKind::FtLockupResumeLockup(vec![FtLockupResumeLockup {
+            id: lockup_id,
+            timestamp,
+            pause_duration,
+        }]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_resume_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "timestamp": timestamp,
+                        "pause_duration": pause_duration,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_prune_lockup() {
+        testing_env!(get_context());
+
+        let lockup_ids: Vec<LockupIndex> = vec![100, 101];
+
+        emit(EventKind::FtLockupPruneLockup(
+            lockup_ids.iter().map(|&id| FtLockupPruneLockup { id }).collect(),
+        ));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_prune_lockup",
+                "data": [{ "id": 100 }, { "id": 101 }],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_set_termination_grace_period() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupSetTerminationGracePeriod(
+            FtLockupSetTerminationGracePeriod { grace_period: 604_800 },
+        ));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_set_termination_grace_period",
+                "data": { "grace_period": 604_800 },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_set_claim_history_limit() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupSetClaimHistoryLimit(FtLockupSetClaimHistoryLimit {
+            limit: 10,
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_set_claim_history_limit",
+                "data": { "limit": 10 },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_transfer_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let old_account_id = AccountId::from_str("alice.near").unwrap();
+        let new_account_id = AccountId::from_str("bob.near").unwrap();
+
+        let event = FtLockupTransferLockup {
+            id: lockup_id,
+            old_account_id,
+            new_account_id,
+        };
+
+        emit(EventKind::FtLockupTransferLockup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_transfer_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "old_account_id": "alice.near",
+                        "new_account_id": "bob.near",
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_split_lockup() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+        let balance: WrappedBalance = 10000.into();
+
+        let event = FtLockupSplitLockup {
+            id: lockup_id,
+            balance,
+            new_lockup_ids: vec![101, 102],
+        };
+
+        emit(EventKind::FtLockupSplitLockup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_split_lockup",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "balance": balance,
+                        "new_lockup_ids": [101, 102],
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_merge_lockups() {
+        testing_env!(get_context());
+
+        let lockup_id: LockupIndex = 100;
+
+        let event = FtLockupMergeLockups {
+            id: lockup_id,
+            merged_lockup_ids: vec![1, 5, 7],
+        };
+
+        emit(EventKind::FtLockupMergeLockups(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_merge_lockups",
+                "data": [
+                    {
+                        "id": lockup_id,
+                        "merged_lockup_ids": [1, 5, 7],
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_create_draft_group() {
+        testing_env!(get_context());
+
+        let draft_group_id: DraftGroupIndex = 22;
+
+        let event = FtLockupCreateDraftGroup { id: draft_group_id };
+
+        emit(EventKind::FtLockupCreateDraftGroup(vec![event]));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_create_draft_group",
+                "data": [
+                    {
+                        "id": draft_group_id,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_create_draft() {
+        testing_env!(get_context());
+
+        let account_id = AccountId::from_str("alice.near").unwrap();
+        let balance: WrappedBalance = 10_000.into();
+        let timestamp: TimestampSec = 1_500_000_000;
+        let lockup_create = LockupCreate {
+            account_id: account_id.clone(),
+            schedule: Schedule::new_unlocked_since(balance.0, timestamp),
+            vesting_schedule: None,
+            terminator_id: None,
+            refund_receiver_id: None,
+        };
+        let draft_group_id: DraftGroupIndex = 123;
+        let draft = Draft {
+            draft_group_id,
+            lockup_create,
+            storage_payment: None,
+        };
+        let draft_id: DraftIndex = 33;
+
+        let event: FtLockupCreateDraft = (draft_id, draft).into();
+
+        emit(EventKind::FtLockupCreateDraft(vec![event]));
+
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_create_draft",
+                "data": [
+                    {
+                        "id": draft_id,
+                        "draft_group_id": draft_group_id,
+                        "account_id": account_id.to_string(),
+                        "balance": balance,
+                        "start": timestamp - 1,
+                        "finish": timestamp,
+                        "terminatable": false,
+                    },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_fund_draft_group() {
         testing_env!(get_context());
 
         let draft_group_id: DraftGroupIndex = 22;
@@ -567,4 +1299,99 @@ mod tests {
             }),
         );
     }
+
+    #[test]
+    fn test_ft_lockup_propose_manager() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupProposeManager(FtLockupProposeManager {
+            manager: Some(AccountId::from_str("new_manager.near").unwrap()),
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_propose_manager",
+                "data": { "manager": "new_manager.near" },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_accept_manager() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupAcceptManager(FtLockupAcceptManager {
+            manager: AccountId::from_str("new_manager.near").unwrap(),
+            old_manager: AccountId::from_str("manager.near").unwrap(),
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_accept_manager",
+                "data": { "manager": "new_manager.near", "old_manager": "manager.near" },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_stage_update() {
+        testing_env!(get_context());
+
+        let code_hash = Base58CryptoHash::from([1; 32]);
+        let activation_timestamp: TimestampSec = 1_800_000_000;
+
+        emit(EventKind::FtLockupStageUpdate(FtLockupStageUpdate {
+            code_hash,
+            activation_timestamp,
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_stage_update",
+                "data": { "code_hash": code_hash, "activation_timestamp": activation_timestamp },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_cancel_update() {
+        testing_env!(get_context());
+
+        let code_hash = Base58CryptoHash::from([1; 32]);
+
+        emit(EventKind::FtLockupCancelUpdate(FtLockupCancelUpdate { code_hash }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_cancel_update",
+                "data": { "code_hash": code_hash },
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ft_lockup_set_update_delay() {
+        testing_env!(get_context());
+
+        emit(EventKind::FtLockupSetUpdateDelay(FtLockupSetUpdateDelay {
+            update_delay: 604_800,
+        }));
+        compare_json(
+            &test_utils::get_logs()[0],
+            json!({
+                "standard": PACKAGE_NAME,
+                "version": VERSION,
+                "event": "ft_lockup_set_update_delay",
+                "data": { "update_delay": 604_800 },
+            }),
+        );
+    }
 }
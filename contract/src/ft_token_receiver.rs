@@ -1,4 +1,4 @@
-use hodl_model::ft_message::FtMessage;
+use hodl_model::{ft_message::FtMessage, lockup::LockupToken, pause::PausableOperation, role::Role};
 use near_sdk::Gas;
 
 use crate::{
@@ -9,17 +9,23 @@ use crate::{
 #[near_bindgen]
 impl FungibleTokenReceiver for Contract {
     fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
-        assert_eq!(env::predecessor_account_id(), self.token_account_id, "Invalid token ID");
+        self.assert_not_paused(PausableOperation::CreateLockup);
+        let token_account_id = env::predecessor_account_id();
+        self.assert_token_whitelist(&token_account_id);
+        // lockups of the default token don't store it
+        let token_account_id = (token_account_id != self.token_account_id).then_some(token_account_id);
+        let token = token_account_id.clone().map(LockupToken::Ft);
         let amount = amount.into();
-        self.assert_deposit_whitelist(&sender_id);
+        self.assert_role(&sender_id, Role::LockupCreator);
 
         let ft_message: FtMessage = serde_json::from_str(&msg).unwrap();
 
         match ft_message {
             FtMessage::LockupCreate(lockup_create) => {
-                let lockup = lockup_create.into_lockup(&sender_id);
+                let mut lockup = lockup_create.into_lockup(&sender_id);
+                lockup.token = token;
                 lockup.assert_new_valid(amount);
-                let index = self.internal_add_lockup(&lockup);
+                let index = self.internal_add_paid_lockup(&mut lockup, &sender_id);
                 log!("Created new lockup for {} with index {}", lockup.account_id, index);
                 let event: FtLockupCreateLockup = (index, lockup, None).into();
                 emit(EventKind::FtLockupCreateLockup(vec![event]));
@@ -34,7 +40,7 @@ impl FungibleTokenReceiver for Contract {
                     draft_group.total_amount, amount,
                     "The draft group total balance doesn't match the transferred balance",
                 );
-                draft_group.fund(&sender_id);
+                draft_group.fund(&sender_id, token_account_id);
                 self.draft_groups.insert(&draft_group_id as _, &draft_group);
                 log!("Funded draft group {}", draft_group_id);
 
@@ -1,28 +1,393 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use hodl_model::lockup::{Lockup, LockupIndex};
+use hodl_model::{
+    lockup::{ClaimRecord, Lockup, LockupClaim, LockupIndex, LockupToken},
+    pause::PausableOperation,
+    role::Role,
+    u256::U256,
+    util::current_timestamp_sec,
+    Balance, TokenAccountId, WrappedBalance,
+};
 
-use crate::{AccountId, Contract};
+use crate::{
+    env,
+    event::{emit, EventKind, FtLockupClaimLockup, FtLockupGrantRole, FtLockupRevokeRole, FtLockupTransferLockup},
+    log, AccountId, Contract, FtTransferPromise, NearToken, Promise, StorageKey, UnorderedSet, MAX_BPS,
+};
 
 impl Contract {
-    pub(crate) fn assert_deposit_whitelist(&self, account_id: &AccountId) {
-        assert!(self.deposit_whitelist.contains(account_id), "Not in deposit whitelist");
+    pub(crate) fn internal_has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.role_members
+            .get(&role)
+            .map_or(false, |members| members.contains(account_id))
     }
 
-    pub(crate) fn assert_draft_operators_whitelist(&self, account_id: &AccountId) {
+    pub(crate) fn assert_role(&self, account_id: &AccountId, role: Role) {
         assert!(
-            (self.deposit_whitelist.contains(account_id) || self.draft_operators_whitelist.contains(account_id)),
-            "Not in draft operators whitelist"
+            self.internal_has_role(account_id, role),
+            "The account doesn't have the {role:?} role"
         );
     }
 
+    /// The manager can act as an admin, so the contract can't be left without one.
+    pub(crate) fn assert_admin(&self, account_id: &AccountId) {
+        if *account_id != self.manager {
+            self.assert_role(account_id, Role::Admin);
+        }
+    }
+
+    pub(crate) fn internal_grant_role(&mut self, role: Role, account_ids: Vec<AccountId>) {
+        let mut members = self
+            .role_members
+            .get(&role)
+            .unwrap_or_else(|| UnorderedSet::new(StorageKey::RoleMembers { role }));
+        members.extend(account_ids.iter().cloned());
+        self.role_members.insert(&role, &members);
+        emit(EventKind::FtLockupGrantRole(FtLockupGrantRole { role, account_ids }));
+    }
+
+    pub(crate) fn internal_revoke_role(&mut self, role: Role, account_ids: Vec<AccountId>) {
+        let Some(mut members) = self.role_members.get(&role) else {
+            return;
+        };
+        for account_id in &account_ids {
+            members.remove(account_id);
+        }
+        assert!(role != Role::Admin || !members.is_empty(), "cannot remove all admins");
+        self.role_members.insert(&role, &members);
+        emit(EventKind::FtLockupRevokeRole(FtLockupRevokeRole { role, account_ids }));
+    }
+
+    /// The fee should be less than the claimed amount and within the limit set by the admins.
+    pub(crate) fn assert_relayer_fee(&self, fee: Balance, claimed_amount: Balance) {
+        assert!(fee < claimed_amount, "The fee should be less than the claimed amount");
+        assert!(
+            U256::from(fee) * U256::from(MAX_BPS) <= U256::from(claimed_amount) * U256::from(self.max_relayer_fee_bps),
+            "The fee exceeds {} basis points of the claimed amount",
+            self.max_relayer_fee_bps
+        );
+    }
+
+    /// The lockup's own terminator if it has one, otherwise any account with the terminator role.
+    pub(crate) fn assert_can_terminate(&self, lockup: &Lockup, account_id: &AccountId) {
+        match lockup
+            .termination_config
+            .as_ref()
+            .and_then(|config| config.terminator_id.as_ref())
+        {
+            Some(terminator_id) => assert_eq!(terminator_id, account_id, "Not the lockup terminator"),
+            None => self.assert_role(account_id, Role::Terminator),
+        }
+    }
+
+    pub(crate) fn assert_updater(&self, account_id: &AccountId) {
+        assert!(
+            *account_id == self.manager || self.internal_has_role(account_id, Role::Updater),
+            "Only the manager or an updater can update the code"
+        );
+    }
+
+    pub(crate) fn assert_pauser(&self, account_id: &AccountId) {
+        if *account_id != self.manager {
+            self.assert_role(account_id, Role::Pauser);
+        }
+    }
+
+    pub(crate) fn assert_not_paused(&self, operation: PausableOperation) {
+        assert!(
+            !self.paused_operations.contains(&operation),
+            "The operation {operation:?} is paused"
+        );
+    }
+
+    /// The lockups and drafts are incomplete until all of them are moved to the current state layout.
+    pub(crate) fn assert_state_migrated(&self) {
+        assert!(self.state_migration.is_none(), "The state is not migrated yet");
+    }
+
+    pub(crate) fn assert_token_whitelist(&self, token_account_id: &TokenAccountId) {
+        assert!(
+            *token_account_id == self.token_account_id || self.token_whitelist.contains(token_account_id),
+            "Not in token whitelist"
+        );
+    }
+
+    /// Resolves the asset of a lockup, `None` stands for the default token.
+    pub(crate) fn internal_lockup_token(&self, token: Option<&LockupToken>) -> LockupToken {
+        token
+            .cloned()
+            .unwrap_or_else(|| LockupToken::Ft(self.token_account_id.clone()))
+    }
+
+    /// The form a lockup stores its asset in, see `internal_lockup_token`.
+    pub(crate) fn internal_stored_lockup_token(&self, token: LockupToken) -> Option<LockupToken> {
+        (token != LockupToken::Ft(self.token_account_id.clone())).then_some(token)
+    }
+
+    /// Transfers `amount` of the asset, the result is handled the same way for any asset.
+    pub(crate) fn internal_transfer(
+        token: LockupToken,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Promise {
+        match token {
+            LockupToken::Ft(token_account_id) => Promise::new(token_account_id).ft_transfer(receiver_id, amount, memo),
+            LockupToken::Native => Promise::new(receiver_id.clone()).transfer(NearToken::from_yoctonear(amount)),
+        }
+    }
+
     pub(crate) fn internal_add_lockup(&mut self, lockup: &Lockup) -> LockupIndex {
+        self.assert_state_migrated();
         let index = LockupIndex::try_from(self.lockups.len()).unwrap();
         self.lockups.push(lockup);
+        // a fully claimed lockup has nothing left to claim, so it's not indexed
+        if lockup.claimed_balance < lockup.schedule.total_balance() {
+            let mut indices = self.account_lockups.get(&lockup.account_id).unwrap_or_default();
+            indices.insert(index);
+            self.internal_save_account_lockups(&lockup.account_id, indices);
+        }
+        index
+    }
+
+    /// Marks the requested amounts of the account's lockups as claimed.
+    /// Returns claims of the lockups with a positive claim amount and their total amount, grouped by token.
+    pub(crate) fn internal_claim_lockups(
+        &mut self,
+        account_id: &AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> HashMap<LockupToken, (Vec<LockupClaim>, Balance)> {
+        let (claim_amounts, mut lockups_by_id) = if let Some(amounts) = amounts {
+            let lockups_by_id: HashMap<LockupIndex, Lockup> = self
+                .internal_get_account_lockups_by_id(account_id, &amounts.iter().map(|x| x.0).collect())
+                .into_iter()
+                .collect();
+            let amounts: HashMap<LockupIndex, WrappedBalance> = amounts
+                .into_iter()
+                .map(|(lockup_id, amount)| {
+                    (
+                        lockup_id,
+                        if let Some(amount) = amount {
+                            amount
+                        } else {
+                            let lockup = lockups_by_id.get(&lockup_id).expect("lockup not found");
+                            let unlocked_balance = lockup.unlocked_balance(current_timestamp_sec());
+                            (unlocked_balance - lockup.claimed_balance).into()
+                        },
+                    )
+                })
+                .collect();
+            (amounts, lockups_by_id)
+        } else {
+            let lockups_by_id: HashMap<LockupIndex, Lockup> =
+                self.internal_get_account_lockups(account_id).into_iter().collect();
+            let amounts: HashMap<LockupIndex, WrappedBalance> = lockups_by_id
+                .iter()
+                .map(|(lockup_id, lockup)| {
+                    let unlocked_balance = lockup.unlocked_balance(current_timestamp_sec());
+                    let amount: WrappedBalance = (unlocked_balance - lockup.claimed_balance).into();
+
+                    (*lockup_id, amount)
+                })
+                .collect();
+            (amounts, lockups_by_id)
+        };
+
+        let mut claims_by_token: HashMap<LockupToken, (Vec<LockupClaim>, Balance)> = HashMap::new();
+        for (lockup_index, lockup_claim_amount) in claim_amounts {
+            let lockup = lockups_by_id.get_mut(&lockup_index).unwrap();
+            let lockup_claim = lockup.claim(lockup_index, lockup_claim_amount.0);
+
+            if lockup_claim.claim_amount.0 > 0 {
+                log!("Claiming {} form lockup #{}", lockup_claim.claim_amount.0, lockup_index);
+                self.lockups.replace(u64::from(lockup_index), lockup);
+                self.internal_add_claim_in_flight(lockup_index, lockup_claim.claim_amount.0);
+                let token = self.internal_lockup_token(lockup.token.as_ref());
+                let (lockup_claims, total_claim_amount) = claims_by_token.entry(token).or_default();
+                *total_claim_amount += lockup_claim.claim_amount.0;
+                lockup_claims.push(lockup_claim);
+            }
+        }
+        for (token, (_, total_claim_amount)) in &claims_by_token {
+            log!("Total claim {} of {}", total_claim_amount, token);
+        }
+
+        claims_by_token
+    }
+
+    /// Same as `internal_claim_lockups`, for operations that make a single transfer.
+    /// Returns the token, claims of the lockups and the total amount, nothing is claimed if the token is `None`.
+    pub(crate) fn internal_claim_lockups_of_single_token(
+        &mut self,
+        account_id: &AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> Option<(LockupToken, Vec<LockupClaim>, Balance)> {
+        let claims_by_token = self.internal_claim_lockups(account_id, amounts);
+        assert!(
+            claims_by_token.len() <= 1,
+            "Lockups of a single token can be claimed at once"
+        );
+        claims_by_token
+            .into_iter()
+            .next()
+            .map(|(token, (lockup_claims, total_claim_amount))| (token, lockup_claims, total_claim_amount))
+    }
+
+    /// Completes claims whose tokens were transferred: emits the claim events and
+    /// stops indexing fully claimed lockups. Returns the total claimed amount.
+    pub(crate) fn internal_finalize_claims(
+        &mut self,
+        lockup_claims: Vec<LockupClaim>,
+        receiver_id: &AccountId,
+    ) -> Balance {
+        let mut total_balance = 0;
+        let timestamp = current_timestamp_sec();
+        let mut events: Vec<FtLockupClaimLockup> = vec![];
+        for LockupClaim {
+            index,
+            is_final,
+            claim_amount,
+        } in lockup_claims
+        {
+            self.internal_remove_claim_in_flight(index, claim_amount.0);
+            if is_final {
+                let initial_storage_usage = env::storage_usage();
+                // The lockup may have been transferred while the claim was in flight.
+                let mut lockup = self.internal_get_claimed_lockup(index);
+                let mut indices = self.account_lockups.get(&lockup.account_id).unwrap_or_default();
+                indices.remove(&index);
+                self.internal_save_account_lockups(&lockup.account_id, indices);
+                self.internal_refund_lockup_storage(index, &mut lockup, initial_storage_usage);
+            }
+            self.internal_record_claim(
+                index,
+                ClaimRecord {
+                    timestamp,
+                    amount: claim_amount,
+                    receiver_id: receiver_id.clone(),
+                },
+            );
+            total_balance += claim_amount.0;
+            let event = FtLockupClaimLockup {
+                id: index,
+                amount: claim_amount,
+                receiver_id: receiver_id.clone(),
+            };
+            events.push(event);
+        }
+        if !events.is_empty() {
+            emit(EventKind::FtLockupClaimLockup(events));
+        }
+
+        total_balance
+    }
+
+    pub(crate) fn internal_add_claim_in_flight(&mut self, index: LockupIndex, amount: Balance) {
+        let in_flight = self.claims_in_flight.get(&index).unwrap_or_default();
+        self.claims_in_flight.insert(&index, &(in_flight + amount));
+    }
+
+    /// Claims sent before the tracking was introduced aren't recorded, so the amount is only reduced to zero.
+    pub(crate) fn internal_remove_claim_in_flight(&mut self, index: LockupIndex, amount: Balance) {
+        let in_flight = self
+            .claims_in_flight
+            .get(&index)
+            .unwrap_or_default()
+            .saturating_sub(amount);
+        if in_flight == 0 {
+            self.claims_in_flight.remove(&index);
+        } else {
+            self.claims_in_flight.insert(&index, &in_flight);
+        }
+    }
+
+    /// Appends the claim to the history of the lockup, the oldest records over the limit are dropped.
+    /// The storage payer of the lockup pays for the growth of the history if its storage balance covers it,
+    /// otherwise the contract does, since a callback can't fail.
+    pub(crate) fn internal_record_claim(&mut self, index: LockupIndex, record: ClaimRecord) {
+        if self.claim_history_limit == 0 {
+            return;
+        }
+        let initial_storage_usage = env::storage_usage();
+        let mut history = self.claim_history.get(&index).unwrap_or_default();
+        history.push(record);
+        let excess = history.len().saturating_sub(self.claim_history_limit as usize);
+        history.drain(..excess);
+        self.claim_history.insert(&index, &history);
+
+        let bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        // lockups that aren't moved by the state migration yet have no payer
+        let Some(mut lockup) = self.lockups.get(u64::from(index)) else {
+            return;
+        };
+        let Some(payment) = lockup.storage_payment.as_mut() else {
+            return;
+        };
+        let payer_id = payment.payer_id.clone();
+        let paid_bytes = self.internal_reserve_storage(&payer_id, bytes);
+        if paid_bytes > 0 {
+            payment.bytes += paid_bytes;
+            self.lockups.replace(u64::from(index), &lockup);
+        }
+    }
+
+    /// Returns a lockup of a claim in flight, which could be sent before the state migration,
+    /// so the lockup isn't necessarily moved yet.
+    fn internal_get_claimed_lockup(&self, index: LockupIndex) -> Lockup {
+        self.lockups.get(u64::from(index)).unwrap_or_else(|| {
+            self.state_migration
+                .as_ref()
+                .and_then(|migration| migration.get_old_lockup(index))
+                .expect("Lockup not found")
+        })
+    }
+
+    /// Returns the amounts of claims whose tokens weren't transferred back to their lockups.
+    pub(crate) fn internal_rollback_claims(&mut self, lockup_claims: Vec<LockupClaim>) {
+        for LockupClaim {
+            index, claim_amount, ..
+        } in lockup_claims
+        {
+            self.internal_remove_claim_in_flight(index, claim_amount.0);
+            let mut lockup = self.internal_get_claimed_lockup(index);
+            lockup.claimed_balance -= claim_amount.0;
+            if u64::from(index) < self.lockups.len() {
+                self.lockups.replace(u64::from(index), &lockup);
+            } else {
+                let migration = self.state_migration.as_mut().expect("Lockup not found");
+                migration.set_old_claimed_balance(index, lockup.claimed_balance);
+            }
+
+            // The lockup may have been transferred while the claim was in flight.
+            let mut indices = self.account_lockups.get(&lockup.account_id).unwrap_or_default();
+            if indices.insert(index) {
+                self.internal_save_account_lockups(&lockup.account_id, indices);
+            }
+        }
+    }
+
+    pub(crate) fn internal_transfer_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        mut lockup: Lockup,
+        receiver_id: AccountId,
+    ) {
+        let old_account_id = std::mem::replace(&mut lockup.account_id, receiver_id);
+
+        let mut indices = self.account_lockups.get(&old_account_id).unwrap_or_default();
+        assert!(indices.remove(&lockup_index), "The lockup is already fully claimed");
+        self.internal_save_account_lockups(&old_account_id, indices);
+
         let mut indices = self.account_lockups.get(&lockup.account_id).unwrap_or_default();
-        indices.insert(index);
+        indices.insert(lockup_index);
         self.internal_save_account_lockups(&lockup.account_id, indices);
-        index
+
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+        emit(EventKind::FtLockupTransferLockup(vec![FtLockupTransferLockup {
+            id: lockup_index,
+            old_account_id,
+            new_account_id: lockup.account_id,
+        }]));
     }
 
     pub(crate) fn internal_save_account_lockups(&mut self, account_id: &AccountId, indices: HashSet<LockupIndex>) {
@@ -33,7 +398,13 @@ impl Contract {
         }
     }
 
+    pub(crate) fn internal_get_lockup(&self, lockup_index: LockupIndex) -> Lockup {
+        self.assert_state_migrated();
+        self.lockups.get(u64::from(lockup_index)).expect("Lockup not found")
+    }
+
     pub(crate) fn internal_get_account_lockups(&self, account_id: &AccountId) -> Vec<(LockupIndex, Lockup)> {
+        self.assert_state_migrated();
         self.account_lockups
             .get(account_id)
             .unwrap_or_default()
@@ -47,6 +418,7 @@ impl Contract {
         account_id: &AccountId,
         lockup_ids: &HashSet<LockupIndex>,
     ) -> Vec<(LockupIndex, Lockup)> {
+        self.assert_state_migrated();
         let account_lockup_ids = self.account_lockups.get(account_id).unwrap_or_default();
 
         lockup_ids
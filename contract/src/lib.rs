@@ -5,11 +5,16 @@ use std::{
 
 use hodl_model::{
     draft::{Draft, DraftGroup, DraftGroupIndex, DraftIndex},
-    lockup::{Lockup, LockupIndex},
+    lockup::{ClaimRecord, Lockup, LockupClaim, LockupCreate, LockupIndex, LockupToken},
     lockup_api::LockupApi,
+    pause::PausableOperation,
+    role::Role,
     schedule::Schedule,
+    storage::{StorageBalance, StoragePayment},
+    termination::{LockupReduction, PendingTermination},
+    update::StagedUpdate,
     util::current_timestamp_sec,
-    TimestampSec, TokenAccountId, WrappedBalance,
+    Balance, TimestampSec, TokenAccountId, WrappedBalance,
 };
 // use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
@@ -20,14 +25,15 @@ use near_sdk::{
     json_types::{Base58CryptoHash, U128},
     log, near, near_bindgen,
     serde::Serialize,
-    serde_json, AccountId, BorshStorageKey, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue,
+    serde_json, AccountId, BorshStorageKey, CryptoHash, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue,
 };
-use near_self_update_proc::SelfUpdate;
 
 pub mod callbacks;
 pub mod event;
 pub mod ft_token_receiver;
 pub mod internal;
+pub mod storage;
+pub mod update;
 
 mod migration;
 pub mod view;
@@ -35,40 +41,74 @@ pub mod view;
 use crate::{
     callbacks::{ext_self, SelfCallbacks},
     event::{
-        emit, EventKind, FtLockupAddToDepositWhitelist, FtLockupAddToDraftOperatorsWhitelist, FtLockupClaimLockup,
-        FtLockupCreateDraft, FtLockupCreateDraftGroup, FtLockupCreateLockup, FtLockupDeleteDraft,
-        FtLockupDiscardDraftGroup, FtLockupFundDraftGroup, FtLockupNew, FtLockupRemoveFromDepositWhitelist,
-        FtLockupRemoveFromDraftOperatorsWhitelist, FtLockupTerminateLockup,
+        emit, EventKind, FtLockupAddToDepositWhitelist, FtLockupAddToDraftOperatorsWhitelist,
+        FtLockupAddToRelayersWhitelist, FtLockupAddToTokenWhitelist, FtLockupCancelLockupTransfer,
+        FtLockupCancelTermination, FtLockupCreateDraft, FtLockupCreateDraftGroup, FtLockupCreateLockup,
+        FtLockupDeleteDraft, FtLockupDiscardDraftGroup, FtLockupFundDraftGroup, FtLockupMergeLockups, FtLockupNew,
+        FtLockupPauseLockup, FtLockupPauseOperations, FtLockupPruneLockup, FtLockupReduceLockup,
+        FtLockupRemoveFromDepositWhitelist, FtLockupRemoveFromDraftOperatorsWhitelist,
+        FtLockupRemoveFromRelayersWhitelist, FtLockupRemoveFromTokenWhitelist, FtLockupRequestLockupTransfer,
+        FtLockupResumeLockup, FtLockupScheduleTermination, FtLockupSetClaimHistoryLimit, FtLockupSetMaxRelayerFee,
+        FtLockupSetTerminationGracePeriod, FtLockupSplitLockup, FtLockupTerminateLockup, FtLockupTransferLockup,
+        FtLockupUnpauseOperations,
     },
+    migration::{write_state_version, StateMigration},
     serde_json::json,
+    storage::REFUND_LOCKUP_STORAGE,
 };
 
 pub const PACKAGE_NAME: &str = env!("CARGO_PKG_NAME");
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_gas(15_000_000_000_000);
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas::from_gas(60_000_000_000_000);
 const GAS_FOR_AFTER_FT_TRANSFER: Gas = Gas::from_gas(20_000_000_000_000);
+const GAS_FOR_AFTER_CLAIMS: Gas = Gas::from_gas(5_000_000_000_000);
+const GAS_FOR_AFTER_CLAIM_FOR: Gas = Gas::from_gas(60_000_000_000_000);
 const GAS_EXT_CALL_COST: Gas = Gas::from_gas(10_000_000_000_000);
 const GAS_MIN_FOR_CONVERT: Gas = Gas::from_gas(15_000_000_000_000);
+/// An auto-claim transfer with its callback and the cost of scheduling them.
+const GAS_FOR_AUTO_CLAIM: Gas = Gas::from_gas(45_000_000_000_000);
+const GAS_FOR_AFTER_AUTO_CLAIMS: Gas = Gas::from_gas(5_000_000_000_000);
+
+/// Basis points in the whole, used for the relayer fee limit
+pub(crate) const MAX_BPS: u16 = 10_000;
 
 #[near(contract_state)]
-#[derive(PanicOnDefault, SelfUpdate)]
+#[derive(PanicOnDefault)]
 pub struct Contract {
+    /// The default token of lockups
     pub token_account_id: TokenAccountId,
 
+    /// tokens that are accepted for lockups in addition to the default one
+    pub token_whitelist: UnorderedSet<TokenAccountId>,
+
     pub lockups: Vector<Lockup>,
 
     pub account_lockups: LookupMap<AccountId, HashSet<LockupIndex>>,
 
-    /// account ids that can perform all actions:
-    /// - manage deposit_whitelist
-    /// - manage drafts, draft_groups
-    /// - create lockups, terminate lockups, fund draft_groups
-    pub deposit_whitelist: UnorderedSet<AccountId>,
+    /// Requested transfers of terminable lockups waiting for an approval from a terminator.
+    pub lockup_transfers: LookupMap<LockupIndex, AccountId>,
+
+    /// account ids that have each role
+    pub role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+
+    /// The maximum fee relayers can take, in basis points of the claimed amount
+    pub max_relayer_fee_bps: u16,
+
+    /// How long a scheduled termination waits before it can be executed,
+    /// terminations are immediate if it's zero
+    pub termination_grace_period: TimestampSec,
+
+    /// Operations stopped by the manager
+    pub paused_operations: HashSet<PausableOperation>,
+
+    /// The latest claims of lockups, at most `claim_history_limit` of them per lockup
+    pub claim_history: LookupMap<LockupIndex, Vec<ClaimRecord>>,
+    pub claim_history_limit: u32,
 
-    /// account ids that can perform all actions on drafts:
-    /// - manage drafts, draft_groups
-    pub draft_operators_whitelist: UnorderedSet<AccountId>,
+    /// Amounts of lockups whose transfers are not complete yet
+    pub claims_in_flight: LookupMap<LockupIndex, Balance>,
 
     pub next_draft_id: DraftIndex,
     pub drafts: LookupMap<DraftIndex, Draft>,
@@ -77,26 +117,181 @@ pub struct Contract {
 
     /// The account ID authorized to perform sensitive operations on the contract.
     pub manager: AccountId,
+
+    /// The account ID that becomes the manager once it accepts the role
+    pub proposed_manager: Option<AccountId>,
+
+    /// How long a staged update waits before it can be applied, updates are not timelocked if it's zero
+    pub update_delay: TimestampSec,
+    pub staged_update: Option<StagedUpdate>,
+
+    /// Deposits of lockup creators and draft operators that pay for storage of lockups and drafts
+    pub storage_balances: LookupMap<AccountId, StorageBalance>,
+
+    /// Lockups and drafts of the previous state layout that are not migrated yet
+    pub(crate) state_migration: Option<StateMigration>,
 }
 
 #[near(serializers=[borsh, json])]
 #[derive(BorshStorageKey)]
 pub(crate) enum StorageKey {
+    // Lockups, DepositWhitelist, DraftOperatorsWhitelist, Drafts and DraftGroups of the version 1 state,
+    // RelayersWhitelist is replaced with the relayer role
     Lockups,
     AccountLockups,
     DepositWhitelist,
     DraftOperatorsWhitelist,
     Drafts,
     DraftGroups,
+    LockupTransfers,
+    RelayersWhitelist,
+    TokenWhitelist,
+    Roles,
+    RoleMembers { role: Role },
+    LockupsV2,
+    DraftsV2,
+    DraftGroupsV2,
+    StorageBalances,
+    ClaimHistory,
+    ClaimsInFlight,
 }
 
 impl Contract {
+    /// Only `update_contract` is subject to it, the manager is changed with `ManagerApi`.
     fn assert_account_can_update(&self) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.manager,
-            "Only the manager can update the code"
-        );
+        self.assert_updater(&env::predecessor_account_id());
+        if self.update_delay > 0 {
+            let staged_update = self.staged_update.as_ref().expect("The update should be staged first");
+            assert!(
+                current_timestamp_sec() >= staged_update.activation_timestamp,
+                "The staged update is not active yet"
+            );
+            // `update_contract` takes the code as its raw input
+            let code_hash: CryptoHash = staged_update.code_hash.into();
+            assert_eq!(
+                env::sha256_array(&env::input().expect("Expected the code")),
+                code_hash,
+                "The code doesn't match the staged update"
+            );
+        }
+    }
+
+    fn internal_claim(
+        &mut self,
+        receiver_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        self.assert_not_paused(PausableOperation::Claim);
+        let account_id = env::predecessor_account_id();
+        let claims_by_token = self.internal_claim_lockups(&account_id, amounts);
+
+        let mut promises: Vec<Promise> = claims_by_token
+            .into_iter()
+            .map(|(token, (lockup_claims, total_claim_amount))| {
+                Self::internal_transfer(
+                    token,
+                    &receiver_id,
+                    total_claim_amount,
+                    Some(format!(
+                        "Claiming unlocked {} balance from {}",
+                        total_claim_amount,
+                        env::current_account_id()
+                    )),
+                )
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                        .after_ft_transfer(account_id.clone(), lockup_claims, Some(receiver_id.clone())),
+                )
+            })
+            .collect();
+
+        match promises.len() {
+            0 => PromiseOrValue::Value(0.into()),
+            1 => promises.pop().unwrap().into(),
+            // Transfers of several tokens are joined, the callback sums up the amounts they have claimed
+            _ => promises
+                .into_iter()
+                .reduce(Promise::and)
+                .unwrap()
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_AFTER_CLAIMS)
+                        .after_claims(),
+                )
+                .into(),
+        }
+    }
+
+    fn internal_terminate(
+        &mut self,
+        lockup_index: LockupIndex,
+        mut lockup: Lockup,
+        hashed_schedule: Option<Schedule>,
+        termination_timestamp: TimestampSec,
+    ) -> PromiseOrValue<WrappedBalance> {
+        let (unvested_balance, beneficiary_id) =
+            lockup.terminate(hashed_schedule, termination_timestamp, current_timestamp_sec());
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+
+        // no need to index an empty or a fully claimed lockup
+        if lockup.claimed_balance == lockup.schedule.total_balance() {
+            let initial_storage_usage = env::storage_usage();
+            let mut indices = self.account_lockups.get(&lockup.account_id).unwrap_or_default();
+            indices.remove(&lockup_index);
+            self.internal_save_account_lockups(&lockup.account_id, indices);
+            self.internal_refund_lockup_storage(lockup_index, &mut lockup, initial_storage_usage);
+        }
+
+        let event = FtLockupTerminateLockup {
+            id: lockup_index,
+            termination_timestamp,
+            unvested_balance: unvested_balance.into(),
+        };
+        emit(EventKind::FtLockupTerminateLockup(vec![event]));
+
+        self.internal_refund_unvested(
+            beneficiary_id,
+            unvested_balance,
+            lockup.token,
+            format!("Terminated lockup #{lockup_index}"),
+        )
+    }
+
+    /// The storage of the predecessor is reserved until the transfer is done,
+    /// it pays for a lockup of the balance if the transfer fails.
+    fn internal_refund_unvested(
+        &mut self,
+        beneficiary_id: AccountId,
+        unvested_balance: Balance,
+        token: Option<LockupToken>,
+        memo: String,
+    ) -> PromiseOrValue<WrappedBalance> {
+        if unvested_balance > 0 {
+            let payer_id = env::predecessor_account_id();
+            // without a storage balance the contract covers the lockup
+            let reserved_bytes = self.internal_reserve_storage(&payer_id, REFUND_LOCKUP_STORAGE);
+            Self::internal_transfer(
+                self.internal_lockup_token(token.as_ref()),
+                &beneficiary_id,
+                unvested_balance,
+                Some(memo),
+            )
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                    .after_lockup_termination(
+                        beneficiary_id,
+                        unvested_balance.into(),
+                        token,
+                        Some(payer_id),
+                        Some(reserved_bytes),
+                    ),
+            )
+            .into()
+        } else {
+            PromiseOrValue::Value(0.into())
+        }
     }
 }
 
@@ -109,109 +304,214 @@ impl LockupApi for Contract {
         draft_operators_whitelist: Option<Vec<AccountId>>,
         manager: AccountId,
     ) -> Self {
-        let mut deposit_whitelist_set = UnorderedSet::new(StorageKey::DepositWhitelist);
-        deposit_whitelist_set.extend(deposit_whitelist.clone().into_iter().map(Into::into));
-        let mut draft_operators_whitelist_set = UnorderedSet::new(StorageKey::DraftOperatorsWhitelist);
-        draft_operators_whitelist_set.extend(
-            draft_operators_whitelist
-                .clone()
-                .unwrap_or_default()
-                .into_iter()
-                .map(Into::into),
-        );
         emit(EventKind::FtLockupNew(FtLockupNew {
             token_account_id: token_account_id.clone(),
         }));
-        emit(EventKind::FtLockupAddToDepositWhitelist(
-            FtLockupAddToDepositWhitelist {
-                account_ids: deposit_whitelist.into_iter().map(Into::into).collect(),
-            },
-        ));
-        emit(EventKind::FtLockupAddToDraftOperatorsWhitelist(
-            FtLockupAddToDraftOperatorsWhitelist {
-                account_ids: draft_operators_whitelist
-                    .unwrap_or_default()
-                    .into_iter()
-                    .map(Into::into)
-                    .collect(),
-            },
-        ));
-        Self {
-            lockups: Vector::new(StorageKey::Lockups),
+        let mut contract = Self {
+            lockups: Vector::new(StorageKey::LockupsV2),
             account_lockups: LookupMap::new(StorageKey::AccountLockups),
+            lockup_transfers: LookupMap::new(StorageKey::LockupTransfers),
             token_account_id,
-            deposit_whitelist: deposit_whitelist_set,
-            draft_operators_whitelist: draft_operators_whitelist_set,
+            token_whitelist: UnorderedSet::new(StorageKey::TokenWhitelist),
+            role_members: LookupMap::new(StorageKey::Roles),
+            max_relayer_fee_bps: 0,
+            termination_grace_period: 0,
+            paused_operations: HashSet::new(),
+            claim_history: LookupMap::new(StorageKey::ClaimHistory),
+            claim_history_limit: 0,
+            claims_in_flight: LookupMap::new(StorageKey::ClaimsInFlight),
             next_draft_id: 0,
-            drafts: LookupMap::new(StorageKey::Drafts),
+            drafts: LookupMap::new(StorageKey::DraftsV2),
             next_draft_group_id: 0,
-            draft_groups: UnorderedMap::new(StorageKey::DraftGroups),
+            draft_groups: UnorderedMap::new(StorageKey::DraftGroupsV2),
             manager,
+            proposed_manager: None,
+            update_delay: 0,
+            staged_update: None,
+            storage_balances: LookupMap::new(StorageKey::StorageBalances),
+            state_migration: None,
+        };
+        write_state_version();
+        for role in Role::DEPOSIT_WHITELIST {
+            contract.internal_grant_role(role, deposit_whitelist.clone());
         }
+        contract.internal_grant_role(Role::DraftOperator, draft_operators_whitelist.unwrap_or_default());
+
+        contract
+    }
+
+    #[payable]
+    fn create_native_lockup(&mut self, lockup_create: LockupCreate) -> LockupIndex {
+        self.assert_not_paused(PausableOperation::CreateLockup);
+        let sender_id = env::predecessor_account_id();
+        self.assert_role(&sender_id, Role::LockupCreator);
+        let amount = env::attached_deposit().as_yoctonear();
+
+        let mut lockup = lockup_create.into_lockup(&sender_id);
+        lockup.token = Some(LockupToken::Native);
+        lockup.assert_new_valid(amount);
+        let index = self.internal_add_paid_lockup(&mut lockup, &sender_id);
+        log!(
+            "Created new native lockup for {} with index {}",
+            lockup.account_id,
+            index
+        );
+        let event: FtLockupCreateLockup = (index, lockup, None).into();
+        emit(EventKind::FtLockupCreateLockup(vec![event]));
+
+        index
     }
 
     fn claim(&mut self, amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>) -> PromiseOrValue<WrappedBalance> {
+        self.internal_claim(env::predecessor_account_id(), amounts)
+    }
+
+    #[payable]
+    fn claim_to(
+        &mut self,
+        receiver_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        assert_one_yocto();
+        self.internal_claim(receiver_id, amounts)
+    }
+
+    #[payable]
+    fn claim_and_call(
+        &mut self,
+        receiver_id: AccountId,
+        msg: String,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        assert_one_yocto();
+        self.assert_not_paused(PausableOperation::Claim);
         let account_id = env::predecessor_account_id();
 
-        let (claim_amounts, mut lockups_by_id) = if let Some(amounts) = amounts {
-            let lockups_by_id: HashMap<LockupIndex, Lockup> = self
-                .internal_get_account_lockups_by_id(&account_id, &amounts.iter().map(|x| x.0).collect())
-                .into_iter()
-                .collect();
-            let amounts: HashMap<LockupIndex, WrappedBalance> = amounts
-                .into_iter()
-                .map(|(lockup_id, amount)| {
-                    (
-                        lockup_id,
-                        if let Some(amount) = amount {
-                            amount
-                        } else {
-                            let lockup = lockups_by_id.get(&lockup_id).expect("lockup not found");
-                            let unlocked_balance = lockup.schedule.unlocked_balance(current_timestamp_sec());
-                            (unlocked_balance - lockup.claimed_balance).into()
-                        },
-                    )
-                })
-                .collect();
-            (amounts, lockups_by_id)
+        if let Some((token, lockup_claims, total_claim_amount)) =
+            self.internal_claim_lockups_of_single_token(&account_id, amounts)
+        {
+            let LockupToken::Ft(token_account_id) = token else {
+                panic!("Native lockups can't be claimed with a call");
+            };
+            Promise::new(token_account_id)
+                .ft_transfer_call(
+                    &receiver_id,
+                    total_claim_amount,
+                    Some(format!(
+                        "Claiming unlocked {total_claim_amount} balance for {account_id}"
+                    )),
+                    msg,
+                )
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
+                        .after_ft_transfer_call(lockup_claims, receiver_id),
+                )
+                .into()
         } else {
-            let lockups_by_id: HashMap<LockupIndex, Lockup> =
-                self.internal_get_account_lockups(&account_id).into_iter().collect();
-            let amounts: HashMap<LockupIndex, WrappedBalance> = lockups_by_id
-                .iter()
-                .map(|(lockup_id, lockup)| {
-                    let unlocked_balance = lockup.schedule.unlocked_balance(current_timestamp_sec());
-                    let amount: WrappedBalance = (unlocked_balance - lockup.claimed_balance).into();
-
-                    (*lockup_id, amount)
-                })
-                .collect();
-            (amounts, lockups_by_id)
-        };
+            PromiseOrValue::Value(0.into())
+        }
+    }
+
+    fn claim_for(
+        &mut self,
+        account_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+        fee: Option<WrappedBalance>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        let relayer_id = env::predecessor_account_id();
+        self.assert_role(&relayer_id, Role::Relayer);
+        self.assert_not_paused(PausableOperation::Claim);
+        let fee = fee.map_or(0, |fee| fee.0);
+        assert!(fee == 0 || self.max_relayer_fee_bps > 0, "Relayers can't take a fee");
 
+        if let Some((token, lockup_claims, total_claim_amount)) =
+            self.internal_claim_lockups_of_single_token(&account_id, amounts)
+        {
+            self.assert_relayer_fee(fee, total_claim_amount);
+            let lockup_token = self.internal_stored_lockup_token(token.clone());
+            Self::internal_transfer(
+                token,
+                &account_id,
+                total_claim_amount - fee,
+                Some(format!(
+                    "Claiming unlocked {} balance from {} by {}",
+                    total_claim_amount - fee,
+                    env::current_account_id(),
+                    relayer_id
+                )),
+            )
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_AFTER_CLAIM_FOR)
+                    .after_claim_for(account_id, lockup_claims, relayer_id, fee.into(), lockup_token),
+            )
+            .into()
+        } else {
+            PromiseOrValue::Value(0.into())
+        }
+    }
+
+    fn set_auto_claim(&mut self, lockup_indices: Vec<LockupIndex>, auto_claim: bool) {
         let account_id = env::predecessor_account_id();
-        let mut lockup_claims = vec![];
-        let mut total_claim_amount = 0;
-        for (lockup_index, lockup_claim_amount) in claim_amounts {
-            let lockup = lockups_by_id.get_mut(&lockup_index).unwrap();
-            let lockup_claim = lockup.claim(lockup_index, lockup_claim_amount.0);
-
-            if lockup_claim.claim_amount.0 > 0 {
-                log!("Claiming {} form lockup #{}", lockup_claim.claim_amount.0, lockup_index);
-                total_claim_amount += lockup_claim.claim_amount.0;
-                self.lockups.replace(u64::from(lockup_index), lockup);
+        for (lockup_index, mut lockup) in
+            self.internal_get_account_lockups_by_id(&account_id, &lockup_indices.into_iter().collect())
+        {
+            lockup.auto_claim = auto_claim;
+            self.lockups.replace(u64::from(lockup_index), &lockup);
+        }
+    }
+
+    fn process_auto_claims(&mut self, from_index: LockupIndex, limit: LockupIndex) -> PromiseOrValue<LockupIndex> {
+        self.assert_not_paused(PausableOperation::Claim);
+        self.assert_state_migrated();
+        let num_lockups = LockupIndex::try_from(self.lockups.len()).unwrap();
+        let to_index = std::cmp::min(from_index.saturating_add(limit), num_lockups);
+        let current_timestamp = current_timestamp_sec();
+        let mut claims_by_account: HashMap<(AccountId, LockupToken), (Vec<LockupClaim>, Balance)> = HashMap::new();
+
+        let mut lockup_index = from_index;
+        while lockup_index < to_index {
+            let mut lockup = self.lockups.get(u64::from(lockup_index)).unwrap();
+            if lockup.pruned || !lockup.auto_claim {
+                lockup_index += 1;
+                continue;
+            }
+            let claim_amount = lockup.unlocked_balance(current_timestamp) - lockup.claimed_balance;
+            if claim_amount > 0 {
+                let key = (
+                    lockup.account_id.clone(),
+                    self.internal_lockup_token(lockup.token.as_ref()),
+                );
+                // Every account gets its own transfer per token, the gas for all of them is reserved,
+                // stopping here if there is no gas left to claim one more lockup
+                let num_transfers = claims_by_account.len() + usize::from(!claims_by_account.contains_key(&key));
+                let required_gas = GAS_FOR_AUTO_CLAIM.as_gas() * num_transfers as u64
+                    + GAS_FOR_AFTER_AUTO_CLAIMS.as_gas()
+                    + GAS_EXT_CALL_COST.as_gas();
+                if env::used_gas().as_gas() + required_gas > env::prepaid_gas().as_gas() {
+                    break;
+                }
+                let lockup_claim = lockup.claim(lockup_index, claim_amount);
+                self.lockups.replace(u64::from(lockup_index), &lockup);
+                self.internal_add_claim_in_flight(lockup_index, claim_amount);
+                let (lockup_claims, total_claim_amount) = claims_by_account.entry(key).or_default();
                 lockup_claims.push(lockup_claim);
+                *total_claim_amount += claim_amount;
             }
+            lockup_index += 1;
         }
-        log!("Total claim {}", total_claim_amount);
 
-        if total_claim_amount > 0 {
-            Promise::new(self.token_account_id.clone())
-                .ft_transfer(
+        let transfers = claims_by_account
+            .into_iter()
+            .map(|((account_id, token), (lockup_claims, total_claim_amount))| {
+                log!("Auto-claiming {} of {} for {}", total_claim_amount, token, account_id);
+                Self::internal_transfer(
+                    token,
                     &account_id,
                     total_claim_amount,
                     Some(format!(
-                        "Claiming unlocked {} balance from {}",
+                        "Auto-claiming unlocked {} balance from {}",
                         total_claim_amount,
                         env::current_account_id()
                     )),
@@ -219,11 +519,20 @@ impl LockupApi for Contract {
                 .then(
                     ext_self::ext(env::current_account_id())
                         .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
-                        .after_ft_transfer(account_id, lockup_claims),
+                        .after_ft_transfer(account_id, lockup_claims, None),
                 )
-                .into()
-        } else {
-            PromiseOrValue::Value(0.into())
+            })
+            .reduce(Promise::and);
+
+        match transfers {
+            Some(transfers) => transfers
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_AFTER_AUTO_CLAIMS)
+                        .after_auto_claims(lockup_index),
+                )
+                .into(),
+            None => PromiseOrValue::Value(lockup_index),
         }
     }
 
@@ -235,62 +544,429 @@ impl LockupApi for Contract {
         termination_timestamp: Option<TimestampSec>,
     ) -> PromiseOrValue<WrappedBalance> {
         assert_one_yocto();
-        self.assert_deposit_whitelist(&env::predecessor_account_id());
-        let mut lockup = self.lockups.get(u64::from(lockup_index)).expect("Lockup not found");
+        assert_eq!(
+            self.termination_grace_period, 0,
+            "A termination should be scheduled first"
+        );
+        let lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        let current_timestamp = current_timestamp_sec();
+        let termination_timestamp = termination_timestamp.unwrap_or(current_timestamp);
+        assert!(
+            termination_timestamp >= current_timestamp,
+            "expected termination_timestamp >= now",
+        );
+        self.internal_terminate(lockup_index, lockup, hashed_schedule, termination_timestamp)
+    }
+
+    #[payable]
+    fn schedule_termination(&mut self, lockup_index: LockupIndex, termination_timestamp: Option<TimestampSec>) {
+        assert_one_yocto();
+        let mut lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        assert!(lockup.termination_config.is_some(), "No termination config");
+        assert!(
+            lockup.pending_termination.is_none(),
+            "The termination is already scheduled"
+        );
         let current_timestamp = current_timestamp_sec();
         let termination_timestamp = termination_timestamp.unwrap_or(current_timestamp);
         assert!(
             termination_timestamp >= current_timestamp,
             "expected termination_timestamp >= now",
         );
-        let (unvested_balance, beneficiary_id) = lockup.terminate(hashed_schedule, termination_timestamp);
+        let pending_termination = PendingTermination {
+            termination_timestamp,
+            executable_at: current_timestamp
+                .checked_add(self.termination_grace_period)
+                .expect("attempt to add with overflow"),
+        };
+        lockup.pending_termination = Some(pending_termination.clone());
         self.lockups.replace(u64::from(lockup_index), &lockup);
 
-        // no need to store empty lockup
-        if lockup.schedule.total_balance() == 0 {
-            let mut indices = self.account_lockups.get(&lockup.account_id).unwrap_or_default();
+        emit(EventKind::FtLockupScheduleTermination(vec![
+            FtLockupScheduleTermination {
+                id: lockup_index,
+                termination_timestamp: pending_termination.termination_timestamp,
+                executable_at: pending_termination.executable_at,
+            },
+        ]));
+    }
+
+    #[payable]
+    fn cancel_termination(&mut self, lockup_index: LockupIndex) {
+        assert_one_yocto();
+        let mut lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        lockup.pending_termination.take().expect("No pending termination");
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+
+        emit(EventKind::FtLockupCancelTermination(vec![FtLockupCancelTermination {
+            id: lockup_index,
+        }]));
+    }
+
+    #[payable]
+    fn execute_termination(
+        &mut self,
+        lockup_index: LockupIndex,
+        hashed_schedule: Option<Schedule>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        assert_one_yocto();
+        let lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        let pending_termination = lockup.pending_termination.clone().expect("No pending termination");
+        assert!(
+            current_timestamp_sec() >= pending_termination.executable_at,
+            "The termination grace period is not over yet"
+        );
+        self.internal_terminate(
+            lockup_index,
+            lockup,
+            hashed_schedule,
+            pending_termination.termination_timestamp,
+        )
+    }
+
+    #[payable]
+    fn reduce_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        reduction: LockupReduction,
+        hashed_schedule: Option<Schedule>,
+    ) -> PromiseOrValue<WrappedBalance> {
+        assert_one_yocto();
+        let mut lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        let timestamp = current_timestamp_sec();
+        let (reduced_balance, beneficiary_id) = lockup.reduce(reduction, hashed_schedule, timestamp);
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+
+        emit(EventKind::FtLockupReduceLockup(vec![FtLockupReduceLockup {
+            id: lockup_index,
+            timestamp,
+            reduced_balance: reduced_balance.into(),
+        }]));
+
+        self.internal_refund_unvested(
+            beneficiary_id,
+            reduced_balance,
+            lockup.token,
+            format!("Reduced lockup #{lockup_index}"),
+        )
+    }
+
+    #[payable]
+    fn pause_lockup(&mut self, lockup_index: LockupIndex) {
+        assert_one_yocto();
+        let mut lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        let timestamp = current_timestamp_sec();
+        lockup.pause(timestamp);
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+
+        emit(EventKind::FtLockupPauseLockup(vec![FtLockupPauseLockup {
+            id: lockup_index,
+            timestamp,
+        }]));
+    }
+
+    #[payable]
+    fn resume_lockup(&mut self, lockup_index: LockupIndex, hashed_schedule: Option<Schedule>) {
+        assert_one_yocto();
+        let mut lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+        let timestamp = current_timestamp_sec();
+        let pause_duration = lockup.resume(hashed_schedule, timestamp);
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+
+        emit(EventKind::FtLockupResumeLockup(vec![FtLockupResumeLockup {
+            id: lockup_index,
+            timestamp,
+            pause_duration,
+        }]));
+    }
+
+    #[payable]
+    fn set_termination_grace_period(&mut self, grace_period: TimestampSec) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        self.termination_grace_period = grace_period;
+        emit(EventKind::FtLockupSetTerminationGracePeriod(
+            FtLockupSetTerminationGracePeriod { grace_period },
+        ));
+    }
+
+    #[payable]
+    fn set_claim_history_limit(&mut self, limit: u32) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        self.claim_history_limit = limit;
+        emit(EventKind::FtLockupSetClaimHistoryLimit(FtLockupSetClaimHistoryLimit {
+            limit,
+        }));
+    }
+
+    #[payable]
+    fn transfer_lockup(&mut self, lockup_index: LockupIndex, receiver_id: AccountId) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        assert_ne!(account_id, receiver_id, "The lockup already belongs to the receiver");
+        let (_, lockup) = self
+            .internal_get_account_lockups_by_id(&account_id, &HashSet::from([lockup_index]))
+            .pop()
+            .unwrap();
+
+        if lockup.termination_config.is_some() {
+            self.lockup_transfers.insert(&lockup_index, &receiver_id);
+            emit(EventKind::FtLockupRequestLockupTransfer(vec![
+                FtLockupRequestLockupTransfer {
+                    id: lockup_index,
+                    account_id,
+                    receiver_id,
+                },
+            ]));
+        } else {
+            self.internal_transfer_lockup(lockup_index, lockup, receiver_id);
+        }
+    }
+
+    #[payable]
+    fn approve_lockup_transfer(&mut self, lockup_index: LockupIndex) {
+        assert_one_yocto();
+        let receiver_id = self
+            .lockup_transfers
+            .remove(&lockup_index)
+            .expect("No pending transfer for the lockup");
+        let lockup = self.internal_get_lockup(lockup_index);
+        self.assert_can_terminate(&lockup, &env::predecessor_account_id());
+
+        self.internal_transfer_lockup(lockup_index, lockup, receiver_id);
+    }
+
+    #[payable]
+    fn cancel_lockup_transfer(&mut self, lockup_index: LockupIndex) {
+        assert_one_yocto();
+        let receiver_id = self
+            .lockup_transfers
+            .remove(&lockup_index)
+            .expect("No pending transfer for the lockup");
+        let lockup = self.internal_get_lockup(lockup_index);
+        let account_id = env::predecessor_account_id();
+        if account_id != lockup.account_id {
+            self.assert_can_terminate(&lockup, &account_id);
+        }
+
+        emit(EventKind::FtLockupCancelLockupTransfer(vec![
+            FtLockupCancelLockupTransfer {
+                id: lockup_index,
+                receiver_id,
+            },
+        ]));
+    }
+
+    #[payable]
+    fn split_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        portions: Vec<(AccountId, WrappedBalance)>,
+    ) -> Vec<LockupIndex> {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let (_, mut lockup) = self
+            .internal_get_account_lockups_by_id(&account_id, &HashSet::from([lockup_index]))
+            .pop()
+            .unwrap();
+        if lockup.termination_config.is_some() {
+            // moving a terminable lockup to another account requires an approval, see `transfer_lockup`
+            assert!(
+                portions.iter().all(|(receiver_id, _)| *receiver_id == account_id),
+                "A terminable lockup can only be split within the owner's account, use transfer_lockup to move a part of it"
+            );
+        }
+
+        let portions: Vec<(AccountId, Balance)> = portions
+            .into_iter()
+            .map(|(receiver_id, balance)| (receiver_id, balance.0))
+            .collect();
+        let new_lockups = lockup.split(&portions);
+        self.lockups.replace(u64::from(lockup_index), &lockup);
+        if lockup.claimed_balance == lockup.schedule.total_balance() {
+            let initial_storage_usage = env::storage_usage();
+            let mut indices = self.account_lockups.get(&account_id).unwrap_or_default();
             indices.remove(&lockup_index);
-            self.internal_save_account_lockups(&lockup.account_id, indices);
+            self.internal_save_account_lockups(&account_id, indices);
+            self.internal_refund_lockup_storage(lockup_index, &mut lockup, initial_storage_usage);
         }
 
-        let event = FtLockupTerminateLockup {
+        let mut events: Vec<FtLockupCreateLockup> = vec![];
+        let new_lockup_ids: Vec<LockupIndex> = new_lockups
+            .into_iter()
+            .map(|mut new_lockup| {
+                let index = self.internal_add_paid_lockup(&mut new_lockup, &account_id);
+                events.push((index, new_lockup, None).into());
+                index
+            })
+            .collect();
+
+        emit(EventKind::FtLockupSplitLockup(vec![FtLockupSplitLockup {
             id: lockup_index,
-            termination_timestamp,
-            unvested_balance: unvested_balance.into(),
-        };
-        emit(EventKind::FtLockupTerminateLockup(vec![event]));
+            balance: lockup.schedule.total_balance().into(),
+            new_lockup_ids: new_lockup_ids.clone(),
+        }]));
+        emit(EventKind::FtLockupCreateLockup(events));
 
-        if unvested_balance > 0 {
-            Promise::new(self.token_account_id.clone())
-                .ft_transfer(
-                    &beneficiary_id.clone(),
-                    unvested_balance,
-                    Some(format!("Terminated lockup #{lockup_index}")),
-                )
-                .then(
-                    ext_self::ext(env::current_account_id())
-                        .with_static_gas(GAS_FOR_AFTER_FT_TRANSFER)
-                        .after_lockup_termination(beneficiary_id, unvested_balance.into()),
-                )
-                .into()
+        new_lockup_ids
+    }
+
+    #[payable]
+    fn merge_lockups(&mut self, lockup_indices: Option<Vec<LockupIndex>>, token: Option<LockupToken>) -> LockupIndex {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let lockups: Vec<(LockupIndex, Lockup)> = if let Some(lockup_indices) = lockup_indices {
+            let lockups = self.internal_get_account_lockups_by_id(&account_id, &lockup_indices.into_iter().collect());
+            assert!(
+                lockups.iter().all(|(_, lockup)| lockup.termination_config.is_none()),
+                "Terminable lockups can't be merged"
+            );
+            // the claim would be rolled back to the replaced lockup if its transfer fails
+            assert!(
+                lockups
+                    .iter()
+                    .all(|(index, _)| !self.claims_in_flight.contains_key(index)),
+                "Lockups with a claim in progress can't be merged"
+            );
+            lockups
         } else {
-            PromiseOrValue::Value(0.into())
+            let token = self.internal_lockup_token(token.as_ref());
+            self.internal_get_account_lockups(&account_id)
+                .into_iter()
+                .filter(|(index, lockup)| {
+                    lockup.termination_config.is_none()
+                        && !self.claims_in_flight.contains_key(index)
+                        && self.internal_lockup_token(lockup.token.as_ref()) == token
+                })
+                .collect()
+        };
+        assert!(lockups.len() >= 2, "At least two lockups are required to merge");
+        let token = lockups[0].1.token.clone();
+        assert!(
+            lockups.iter().all(|(_, lockup)| lockup.token == token),
+            "Lockups of different tokens can't be merged"
+        );
+
+        // The merged schedule unlocks exactly the sum at the current timestamp, so it covers the claimed balance
+        let current_timestamp = current_timestamp_sec();
+        let schedule = Schedule::merge(
+            &lockups.iter().map(|(_, lockup)| &lockup.schedule).collect::<Vec<_>>(),
+            current_timestamp,
+        );
+        schedule.assert_valid(lockups.iter().map(|(_, lockup)| lockup.schedule.total_balance()).sum());
+        let mut merged_lockup = Lockup {
+            account_id: account_id.clone(),
+            schedule,
+            claimed_balance: lockups.iter().map(|(_, lockup)| lockup.claimed_balance).sum(),
+            termination_config: None,
+            auto_claim: lockups.iter().all(|(_, lockup)| lockup.auto_claim),
+            token,
+            pending_termination: None,
+            paused_at: None,
+            storage_payment: None,
+            pruned: false,
+        };
+
+        // The merged lockups keep only the claimed balance, so they are finished and replaced with tombstones
+        let mut indices = self.account_lockups.get(&account_id).unwrap_or_default();
+        let mut merged_lockup_ids = vec![];
+        for (lockup_index, mut lockup) in lockups {
+            let initial_storage_usage = env::storage_usage();
+            if lockup.claimed_balance == 0 {
+                // nothing is left of the lockup, `terminate` would keep its start and stretch it to now
+                lockup.schedule = Schedule::new_zero_balance_from_to(current_timestamp - 1, current_timestamp);
+            } else {
+                lockup.schedule.terminate(lockup.claimed_balance, current_timestamp);
+            }
+            self.lockups.replace(u64::from(lockup_index), &lockup.to_tombstone());
+            if let Some(mut payment) = lockup.storage_payment {
+                self.internal_refund_storage(&mut payment, initial_storage_usage);
+            }
+            indices.remove(&lockup_index);
+            merged_lockup_ids.push(lockup_index);
         }
+        self.internal_save_account_lockups(&account_id, indices);
+
+        let index = self.internal_add_paid_lockup(&mut merged_lockup, &account_id);
+        emit(EventKind::FtLockupMergeLockups(vec![FtLockupMergeLockups {
+            id: index,
+            merged_lockup_ids,
+        }]));
+        emit(EventKind::FtLockupCreateLockup(vec![
+            (index, merged_lockup, None).into()
+        ]));
+
+        index
+    }
+
+    fn prune_lockups(&mut self, lockup_indices: Vec<LockupIndex>) -> Vec<LockupIndex> {
+        self.assert_state_migrated();
+        let mut pruned_lockup_ids = vec![];
+        for lockup_index in lockup_indices {
+            let Some(lockup) = self.lockups.get(u64::from(lockup_index)) else {
+                continue;
+            };
+            // a lockup with a claim in progress stays indexed until the transfer is complete
+            let is_indexed = self
+                .account_lockups
+                .get(&lockup.account_id)
+                .map_or(false, |indices| indices.contains(&lockup_index));
+            if lockup.pruned || !lockup.is_finished() || is_indexed {
+                continue;
+            }
+
+            let initial_storage_usage = env::storage_usage();
+            self.lockup_transfers.remove(&lockup_index);
+            self.lockups.replace(u64::from(lockup_index), &lockup.to_tombstone());
+            if let Some(mut payment) = lockup.storage_payment {
+                self.internal_refund_storage(&mut payment, initial_storage_usage);
+            }
+            pruned_lockup_ids.push(lockup_index);
+        }
+
+        if !pruned_lockup_ids.is_empty() {
+            emit(EventKind::FtLockupPruneLockup(
+                pruned_lockup_ids.iter().map(|&id| FtLockupPruneLockup { id }).collect(),
+            ));
+        }
+
+        pruned_lockup_ids
+    }
+
+    #[payable]
+    fn grant_role(&mut self, role: Role, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        self.internal_grant_role(role, account_ids);
+    }
+
+    #[payable]
+    fn revoke_role(&mut self, role: Role, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        self.internal_revoke_role(role, account_ids);
     }
 
     // preserving both options for API compatibility
     #[payable]
     fn add_to_deposit_whitelist(&mut self, account_id: Option<AccountId>, account_ids: Option<Vec<AccountId>>) {
         assert_one_yocto();
-        self.assert_deposit_whitelist(&env::predecessor_account_id());
+        self.assert_admin(&env::predecessor_account_id());
         let account_ids = if let Some(account_ids) = account_ids {
             account_ids
         } else {
             vec![account_id.expect("expected either account_id or account_ids")]
         };
-        for account_id in &account_ids {
-            self.deposit_whitelist.insert(account_id);
-        }
+        // never touches the admin role, the other roles are managed with `grant_role` and `revoke_role`
+        self.internal_grant_role(Role::LockupCreator, account_ids.clone());
         emit(EventKind::FtLockupAddToDepositWhitelist(
             FtLockupAddToDepositWhitelist {
                 account_ids: account_ids.into_iter().map(Into::into).collect(),
@@ -302,19 +978,14 @@ impl LockupApi for Contract {
     #[payable]
     fn remove_from_deposit_whitelist(&mut self, account_id: Option<AccountId>, account_ids: Option<Vec<AccountId>>) {
         assert_one_yocto();
-        self.assert_deposit_whitelist(&env::predecessor_account_id());
+        self.assert_admin(&env::predecessor_account_id());
         let account_ids = if let Some(account_ids) = account_ids {
             account_ids
         } else {
             vec![account_id.expect("expected either account_id or account_ids")]
         };
-        for account_id in &account_ids {
-            self.deposit_whitelist.remove(account_id);
-        }
-        assert!(
-            !self.deposit_whitelist.is_empty(),
-            "cannot remove all accounts from deposit whitelist",
-        );
+        // never touches the admin role, the other roles are managed with `grant_role` and `revoke_role`
+        self.internal_revoke_role(Role::LockupCreator, account_ids.clone());
         emit(EventKind::FtLockupRemoveFromDepositWhitelist(
             FtLockupRemoveFromDepositWhitelist {
                 account_ids: account_ids.into_iter().map(Into::into).collect(),
@@ -325,10 +996,8 @@ impl LockupApi for Contract {
     #[payable]
     fn add_to_draft_operators_whitelist(&mut self, account_ids: Vec<AccountId>) {
         assert_one_yocto();
-        self.assert_deposit_whitelist(&env::predecessor_account_id());
-        for account_id in &account_ids {
-            self.draft_operators_whitelist.insert(account_id);
-        }
+        self.assert_admin(&env::predecessor_account_id());
+        self.internal_grant_role(Role::DraftOperator, account_ids.clone());
         emit(EventKind::FtLockupAddToDraftOperatorsWhitelist(
             FtLockupAddToDraftOperatorsWhitelist {
                 account_ids: account_ids.into_iter().map(Into::into).collect(),
@@ -339,10 +1008,8 @@ impl LockupApi for Contract {
     #[payable]
     fn remove_from_draft_operators_whitelist(&mut self, account_ids: Vec<AccountId>) {
         assert_one_yocto();
-        self.assert_deposit_whitelist(&env::predecessor_account_id());
-        for account_id in &account_ids {
-            self.draft_operators_whitelist.remove(account_id);
-        }
+        self.assert_admin(&env::predecessor_account_id());
+        self.internal_revoke_role(Role::DraftOperator, account_ids.clone());
         emit(EventKind::FtLockupRemoveFromDraftOperatorsWhitelist(
             FtLockupRemoveFromDraftOperatorsWhitelist {
                 account_ids: account_ids.into_iter().map(Into::into).collect(),
@@ -350,8 +1017,88 @@ impl LockupApi for Contract {
         ));
     }
 
+    #[payable]
+    fn add_to_relayers_whitelist(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        self.internal_grant_role(Role::Relayer, account_ids.clone());
+        emit(EventKind::FtLockupAddToRelayersWhitelist(
+            FtLockupAddToRelayersWhitelist { account_ids },
+        ));
+    }
+
+    #[payable]
+    fn remove_from_relayers_whitelist(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        self.internal_revoke_role(Role::Relayer, account_ids.clone());
+        emit(EventKind::FtLockupRemoveFromRelayersWhitelist(
+            FtLockupRemoveFromRelayersWhitelist { account_ids },
+        ));
+    }
+
+    #[payable]
+    fn set_max_relayer_fee_bps(&mut self, max_fee_bps: u16) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        assert!(
+            max_fee_bps <= MAX_BPS,
+            "The maximum relayer fee should not exceed {MAX_BPS} basis points"
+        );
+        self.max_relayer_fee_bps = max_fee_bps;
+        emit(EventKind::FtLockupSetMaxRelayerFee(FtLockupSetMaxRelayerFee {
+            max_fee_bps,
+        }));
+    }
+
+    #[payable]
+    fn add_to_token_whitelist(&mut self, token_account_ids: Vec<TokenAccountId>) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        for token_account_id in &token_account_ids {
+            self.token_whitelist.insert(token_account_id);
+        }
+        emit(EventKind::FtLockupAddToTokenWhitelist(FtLockupAddToTokenWhitelist {
+            token_account_ids,
+        }));
+    }
+
+    #[payable]
+    fn remove_from_token_whitelist(&mut self, token_account_ids: Vec<TokenAccountId>) {
+        assert_one_yocto();
+        self.assert_admin(&env::predecessor_account_id());
+        for token_account_id in &token_account_ids {
+            self.token_whitelist.remove(token_account_id);
+        }
+        emit(EventKind::FtLockupRemoveFromTokenWhitelist(
+            FtLockupRemoveFromTokenWhitelist { token_account_ids },
+        ));
+    }
+
+    #[payable]
+    fn pause(&mut self, operations: Vec<PausableOperation>) {
+        assert_one_yocto();
+        self.assert_pauser(&env::predecessor_account_id());
+        self.paused_operations.extend(operations.iter().copied());
+        emit(EventKind::FtLockupPauseOperations(FtLockupPauseOperations {
+            operations,
+        }));
+    }
+
+    #[payable]
+    fn unpause(&mut self, operations: Vec<PausableOperation>) {
+        assert_one_yocto();
+        self.assert_pauser(&env::predecessor_account_id());
+        for operation in &operations {
+            self.paused_operations.remove(operation);
+        }
+        emit(EventKind::FtLockupUnpauseOperations(FtLockupUnpauseOperations {
+            operations,
+        }));
+    }
+
     fn create_draft_group(&mut self) -> DraftGroupIndex {
-        self.assert_draft_operators_whitelist(&env::predecessor_account_id());
+        self.assert_role(&env::predecessor_account_id(), Role::DraftOperator);
 
         let index = self.next_draft_group_id;
         self.next_draft_group_id += 1;
@@ -371,12 +1118,14 @@ impl LockupApi for Contract {
     }
 
     fn create_drafts(&mut self, drafts: Vec<Draft>) -> Vec<DraftIndex> {
-        self.assert_draft_operators_whitelist(&env::predecessor_account_id());
+        let payer_id = env::predecessor_account_id();
+        self.assert_role(&payer_id, Role::DraftOperator);
+        let initial_storage_usage = env::storage_usage();
         let mut draft_group_lookup: HashMap<DraftGroupIndex, DraftGroup> = HashMap::new();
         let mut events: Vec<FtLockupCreateDraft> = vec![];
         let draft_ids: Vec<DraftIndex> = drafts
             .into_iter()
-            .map(|draft| {
+            .map(|mut draft| {
                 let draft_group = draft_group_lookup.entry(draft.draft_group_id).or_insert_with(|| {
                     self.draft_groups
                         .get(&draft.draft_group_id as _)
@@ -387,7 +1136,18 @@ impl LockupApi for Contract {
 
                 let index = self.next_draft_id;
                 self.next_draft_id += 1;
+                // the size of the payment doesn't depend on the paid bytes, so it's stored before they are known
+                let draft_storage_usage = env::storage_usage();
+                draft.storage_payment = Some(StoragePayment {
+                    payer_id: payer_id.clone(),
+                    bytes: 0,
+                });
                 assert!(self.drafts.insert(&index, &draft).is_none(), "Invariant");
+                draft.storage_payment = Some(StoragePayment {
+                    payer_id: payer_id.clone(),
+                    bytes: env::storage_usage() - draft_storage_usage,
+                });
+                self.drafts.insert(&index, &draft);
                 draft_group.total_amount = draft_group
                     .total_amount
                     .checked_add(draft.total_balance())
@@ -404,6 +1164,8 @@ impl LockupApi for Contract {
         for (draft_group_id, draft_group) in draft_group_lookup {
             self.draft_groups.insert(&draft_group_id as _, &draft_group);
         }
+        // the draft groups are charged too, but only the drafts are refunded
+        self.internal_charge_storage(&payer_id, env::storage_usage() - initial_storage_usage);
 
         draft_ids
     }
@@ -413,7 +1175,7 @@ impl LockupApi for Contract {
     }
 
     fn discard_draft_group(&mut self, draft_group_id: DraftGroupIndex) {
-        self.assert_draft_operators_whitelist(&env::predecessor_account_id());
+        self.assert_role(&env::predecessor_account_id(), Role::DraftOperator);
 
         let mut draft_group = self
             .draft_groups
@@ -434,10 +1196,15 @@ impl LockupApi for Contract {
 
     fn delete_drafts(&mut self, draft_ids: Vec<DraftIndex>) {
         // no authorization required here since the draft group discard has been authorized
+        self.assert_state_migrated();
         let mut draft_group_lookup: HashMap<DraftGroupIndex, DraftGroup> = HashMap::new();
         let mut events: Vec<FtLockupDeleteDraft> = vec![];
         for draft_id in draft_ids {
-            let draft = self.drafts.remove(&draft_id as _).expect("draft not found");
+            let initial_storage_usage = env::storage_usage();
+            let mut draft = self.drafts.remove(&draft_id as _).expect("draft not found");
+            if let Some(payment) = draft.storage_payment.as_mut() {
+                self.internal_refund_storage(payment, initial_storage_usage);
+            }
             let draft_group = draft_group_lookup.entry(draft.draft_group_id).or_insert_with(|| {
                 self.draft_groups
                     .get(&draft.draft_group_id as _)
@@ -471,6 +1238,14 @@ impl LockupApi for Contract {
 pub type TokenAmount = u128;
 trait FtTransferPromise {
     fn ft_transfer(self, receiver_id: &AccountId, amount: TokenAmount, memo: Option<String>) -> Promise;
+
+    fn ft_transfer_call(
+        self,
+        receiver_id: &AccountId,
+        amount: TokenAmount,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise;
 }
 
 impl FtTransferPromise for Promise {
@@ -489,4 +1264,166 @@ impl FtTransferPromise for Promise {
             GAS_FOR_FT_TRANSFER,
         )
     }
+    fn ft_transfer_call(
+        self,
+        receiver_id: &AccountId,
+        amount: TokenAmount,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise {
+        let args = serde_json::to_vec(&json!({
+            "receiver_id": receiver_id,
+            "amount": amount.to_string(),
+            "memo": memo.unwrap_or_default(),
+            "msg": msg,
+        }))
+        .expect("Failed to serialize arguments");
+
+        self.function_call(
+            "ft_transfer_call".to_string(),
+            args,
+            NearToken::from_yoctonear(1),
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hodl_model::{
+        calendar::CalendarDate, ft_message::FtMessage, storage::StorageApi, termination::VestingConditions,
+    };
+    use near_sdk::{test_utils::VMContextBuilder, testing_env, PromiseResult};
+
+    use super::*;
+
+    fn alice() -> AccountId {
+        "alice.near".parse().unwrap()
+    }
+
+    fn manager() -> AccountId {
+        "manager.near".parse().unwrap()
+    }
+
+    fn date(year: u16, month: u8, day: u8) -> CalendarDate {
+        CalendarDate { year, month, day }
+    }
+
+    fn set_context_on(predecessor_id: AccountId, date: CalendarDate, deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor_id)
+            .attached_deposit(deposit)
+            .block_timestamp(u64::from(date.timestamp()) * 1_000_000_000)
+            .build());
+    }
+
+    fn set_context(deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(alice())
+            .attached_deposit(deposit)
+            .block_timestamp(1_000_000_000_000)
+            .build());
+    }
+
+    /// Alice's lockups of 100 tokens each, the first one has a claim in flight.
+    fn contract_with_claim_in_flight() -> (Contract, Vec<LockupClaim>) {
+        set_context(NearToken::from_near(1));
+        let mut contract = Contract::new("token.near".parse().unwrap(), vec![], None, manager());
+        contract.storage_deposit(None, None);
+        for _ in 0..3 {
+            contract.internal_add_lockup(&Lockup::new_unlocked(alice(), 100));
+        }
+        let (lockup_claims, _) = contract
+            .internal_claim_lockups(&alice(), Some(vec![(0, Some(40.into()))]))
+            .into_values()
+            .next()
+            .unwrap();
+        set_context(NearToken::from_yoctonear(1));
+
+        (contract, lockup_claims)
+    }
+
+    #[test]
+    #[should_panic(expected = "Lockups with a claim in progress can't be merged")]
+    fn merge_lockup_with_claim_in_flight() {
+        let (mut contract, _) = contract_with_claim_in_flight();
+        contract.merge_lockups(Some(vec![0, 1]), None);
+    }
+
+    #[test]
+    fn merge_skips_lockup_with_claim_in_flight() {
+        let (mut contract, lockup_claims) = contract_with_claim_in_flight();
+        let index = contract.merge_lockups(None, None);
+        assert_eq!(3, index);
+        assert_eq!(200, contract.lockups.get(3).unwrap().schedule.total_balance());
+
+        // the failed transfer returns the claim to the lockup, which is still in place
+        contract.internal_rollback_claims(lockup_claims);
+        let lockup = contract.lockups.get(0).unwrap();
+        assert_eq!(0, lockup.claimed_balance);
+        assert_eq!(100, lockup.schedule.total_balance());
+        assert!(!contract.claims_in_flight.contains_key(&0));
+        assert_eq!(HashSet::from([0, 3]), contract.account_lockups.get(&alice()).unwrap());
+    }
+
+    #[test]
+    fn claim_and_call_returns_unused_amount_to_lockup() {
+        let (mut contract, lockup_claims) = contract_with_claim_in_flight();
+
+        // the receiver has used 30 of the 40 claimed tokens
+        testing_env!(
+            VMContextBuilder::new().build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(serde_json::to_vec(&U128(30)).unwrap())],
+        );
+        let used_balance = contract.after_ft_transfer_call(lockup_claims, "receiver.near".parse().unwrap());
+
+        assert_eq!(30, used_balance.0);
+        assert_eq!(30, contract.lockups.get(0).unwrap().claimed_balance);
+        assert!(!contract.claims_in_flight.contains_key(&0));
+    }
+
+    #[test]
+    fn calendar_lockup_from_month_end() {
+        set_context_on(manager(), date(2025, 1, 31), NearToken::from_near(1));
+        let mut contract = Contract::new("token.near".parse().unwrap(), vec![manager()], None, manager());
+        contract.storage_deposit(None, None);
+
+        // 100 tokens unlock on the last day of February, March and April
+        let lockup_create = LockupCreate {
+            account_id: alice(),
+            schedule: Schedule::new_calendar_monthly(300, date(2025, 1, 31), 3),
+            vesting_schedule: Some(VestingConditions::SameAsLockupSchedule),
+            terminator_id: None,
+            refund_receiver_id: None,
+        };
+        set_context_on(
+            "token.near".parse().unwrap(),
+            date(2025, 1, 31),
+            NearToken::from_near(0),
+        );
+        contract.ft_on_transfer(
+            manager(),
+            U128(300),
+            serde_json::to_string(&FtMessage::LockupCreate(lockup_create)).unwrap(),
+        );
+
+        set_context_on(alice(), date(2025, 2, 27), NearToken::from_near(0));
+        assert!(matches!(contract.claim(None), PromiseOrValue::Value(U128(0))));
+
+        set_context_on(alice(), date(2025, 2, 28), NearToken::from_near(0));
+        contract.claim(None);
+        assert_eq!(100, contract.lockups.get(0).unwrap().claimed_balance);
+        assert_eq!(Some(100), contract.claims_in_flight.get(&0));
+
+        // the March step isn't reached yet, so the lockup keeps the claimed balance only
+        set_context_on(manager(), date(2025, 3, 15), NearToken::from_yoctonear(1));
+        contract.terminate(0, None, None);
+        let lockup = contract.lockups.get(0).unwrap();
+        assert_eq!(100, lockup.schedule.total_balance());
+        assert_eq!(date(2025, 2, 28).timestamp(), lockup.schedule.finish_timestamp());
+        assert!(!contract.account_lockups.get(&alice()).unwrap_or_default().contains(&0));
+    }
 }
@@ -1,7 +1,86 @@
-use near_sdk::{env, near_bindgen};
+use std::collections::HashSet;
 
-use crate::{Contract, ContractExt};
-use crate::event::{emit, EventKind, FtLockupUpdateContract};
+use hodl_model::{
+    draft::{Draft, DraftGroup, DraftGroupIndex, DraftIndex},
+    lockup::{Lockup, LockupCreate, LockupIndex},
+    role::Role,
+    schedule::{Checkpoint, Interpolation, Schedule},
+    termination::{TerminationConfig, VestingConditions},
+    update::MigrationApi,
+    Balance, TimestampSec, TokenAccountId,
+};
+use near_sdk::{
+    collections::{LookupMap, UnorderedMap, UnorderedSet, Vector},
+    env,
+    json_types::Base58CryptoHash,
+    near, near_bindgen, AccountId,
+};
+
+use crate::{
+    event::{emit, EventKind, FtLockupCreateLockup, FtLockupUpdateContract},
+    Contract, ContractExt, StorageKey,
+};
+
+/// Holds the version of the state layout, the state of version 1 was stored before the key was introduced.
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+const STATE_VERSION: u8 = 2;
+
+/// The state layouts the contract can be migrated from.
+pub(crate) enum VersionedContract {
+    V1(ContractV1),
+    V2(Contract),
+}
+
+impl VersionedContract {
+    fn read() -> Self {
+        match env::storage_read(STATE_VERSION_KEY).as_deref() {
+            None => Self::V1(env::state_read().expect("Failed to read old state")),
+            Some([STATE_VERSION]) => Self::V2(env::state_read().expect("Failed to read old state")),
+            Some(version) => panic!("Unknown state version {version:?}"),
+        }
+    }
+
+    fn into_current(self) -> Contract {
+        match self {
+            Self::V1(state) => state.into_v2(),
+            Self::V2(state) => state,
+        }
+    }
+}
+
+pub(crate) fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &[STATE_VERSION]);
+}
+
+/// Lockups and drafts of version 1 that are moved to the current layout by `migrate_state`.
+#[near(serializers=[borsh])]
+pub struct StateMigration {
+    old_lockups: Vector<LockupV1>,
+    old_drafts: LookupMap<DraftIndex, DraftV1>,
+    /// The drafts with ids below it are to be migrated
+    old_next_draft_id: DraftIndex,
+    next_draft_id: DraftIndex,
+    /// Lockups created by callbacks during the migration, they are added once it's complete
+    deferred_lockups: Vec<Lockup>,
+}
+
+impl StateMigration {
+    /// Returns a lockup that isn't moved yet, callbacks of calls made before the update can still refer to it.
+    pub(crate) fn get_old_lockup(&self, index: LockupIndex) -> Option<Lockup> {
+        self.old_lockups.get(u64::from(index)).map(Into::into)
+    }
+
+    /// Updates the claimed balance of a lockup that isn't moved yet, the rest of it can't change in a callback.
+    pub(crate) fn set_old_claimed_balance(&mut self, index: LockupIndex, claimed_balance: Balance) {
+        let mut lockup = self.old_lockups.get(u64::from(index)).expect("Lockup not found");
+        lockup.claimed_balance = claimed_balance;
+        self.old_lockups.replace(u64::from(index), &lockup);
+    }
+
+    pub(crate) fn defer_lockup(&mut self, lockup: Lockup) {
+        self.deferred_lockups.push(lockup);
+    }
+}
 
 #[near_bindgen]
 impl Contract {
@@ -10,8 +89,356 @@ impl Contract {
     pub fn migrate() -> Self {
         emit(EventKind::FtLockupUpdateContract(FtLockupUpdateContract {}));
 
-        let old_state: Contract = env::state_read().expect("Failed to read old state");
+        let mut state = VersionedContract::read().into_current();
+        // the staged update is the code being deployed now
+        state.staged_update = None;
+        write_state_version();
+
+        state
+    }
+}
+
+#[near_bindgen]
+impl MigrationApi for Contract {
+    fn migrate_state(&mut self, limit: u32) -> bool {
+        self.assert_updater(&env::predecessor_account_id());
+        let Some(mut migration) = self.state_migration.take() else {
+            return true;
+        };
+
+        let mut limit = limit;
+        // The lockups are copied in order, so they keep their indices
+        while limit > 0 && self.lockups.len() < migration.old_lockups.len() {
+            let lockup = migration.old_lockups.get(self.lockups.len()).unwrap();
+            self.lockups.push(&lockup.into());
+            limit -= 1;
+        }
+        while limit > 0 && migration.next_draft_id < migration.old_next_draft_id {
+            if let Some(draft) = migration.old_drafts.remove(&migration.next_draft_id) {
+                self.drafts.insert(&migration.next_draft_id, &draft.into());
+            }
+            migration.next_draft_id += 1;
+            limit -= 1;
+        }
+        // The copied lockups are removed from the end, no lockups are added until the migration is complete
+        if self.lockups.len() >= migration.old_lockups.len() {
+            while limit > 0 && migration.old_lockups.pop().is_some() {
+                limit -= 1;
+            }
+        }
+
+        let is_complete = migration.old_lockups.is_empty() && migration.next_draft_id == migration.old_next_draft_id;
+        if !is_complete {
+            self.state_migration = Some(migration);
+            return false;
+        }
+
+        let events: Vec<FtLockupCreateLockup> = migration
+            .deferred_lockups
+            .into_iter()
+            .map(|lockup| (self.internal_add_lockup(&lockup), lockup, None).into())
+            .collect();
+        if !events.is_empty() {
+            emit(EventKind::FtLockupCreateLockup(events));
+        }
+        true
+    }
+
+    fn is_state_migrated(&self) -> bool {
+        self.state_migration.is_none()
+    }
+}
+
+#[near(serializers=[borsh])]
+pub struct ContractV1 {
+    token_account_id: TokenAccountId,
+    lockups: Vector<LockupV1>,
+    account_lockups: LookupMap<AccountId, HashSet<LockupIndex>>,
+    deposit_whitelist: UnorderedSet<AccountId>,
+    draft_operators_whitelist: UnorderedSet<AccountId>,
+    next_draft_id: DraftIndex,
+    drafts: LookupMap<DraftIndex, DraftV1>,
+    next_draft_group_id: DraftGroupIndex,
+    draft_groups: UnorderedMap<DraftGroupIndex, DraftGroupV1>,
+    manager: AccountId,
+}
+
+impl ContractV1 {
+    /// Lockups and drafts are left for `migrate_state`, the rest is migrated at once.
+    fn into_v2(self) -> Contract {
+        let Self {
+            token_account_id,
+            lockups,
+            account_lockups,
+            mut deposit_whitelist,
+            mut draft_operators_whitelist,
+            next_draft_id,
+            drafts,
+            next_draft_group_id,
+            mut draft_groups,
+            manager,
+        } = self;
+
+        // there are only a few draft groups in use at a time
+        let mut new_draft_groups = UnorderedMap::new(StorageKey::DraftGroupsV2);
+        for (index, draft_group) in draft_groups.iter() {
+            new_draft_groups.insert(&index, &draft_group.into());
+        }
+        draft_groups.clear();
+
+        let mut state = Contract {
+            token_account_id,
+            token_whitelist: UnorderedSet::new(StorageKey::TokenWhitelist),
+            lockups: Vector::new(StorageKey::LockupsV2),
+            account_lockups,
+            lockup_transfers: LookupMap::new(StorageKey::LockupTransfers),
+            role_members: LookupMap::new(StorageKey::Roles),
+            max_relayer_fee_bps: 0,
+            termination_grace_period: 0,
+            paused_operations: HashSet::new(),
+            claim_history: LookupMap::new(StorageKey::ClaimHistory),
+            claim_history_limit: 0,
+            claims_in_flight: LookupMap::new(StorageKey::ClaimsInFlight),
+            next_draft_id,
+            drafts: LookupMap::new(StorageKey::DraftsV2),
+            next_draft_group_id,
+            draft_groups: new_draft_groups,
+            manager,
+            proposed_manager: None,
+            update_delay: 0,
+            staged_update: None,
+            storage_balances: LookupMap::new(StorageKey::StorageBalances),
+            state_migration: Some(StateMigration {
+                old_lockups: lockups,
+                old_drafts: drafts,
+                old_next_draft_id: next_draft_id,
+                next_draft_id: 0,
+                deferred_lockups: vec![],
+            }),
+        };
+
+        let deposit_whitelist_members = deposit_whitelist.to_vec();
+        if !deposit_whitelist_members.is_empty() {
+            for role in Role::DEPOSIT_WHITELIST {
+                state.internal_grant_role(role, deposit_whitelist_members.clone());
+            }
+        }
+        let draft_operators = draft_operators_whitelist.to_vec();
+        if !draft_operators.is_empty() {
+            state.internal_grant_role(Role::DraftOperator, draft_operators);
+        }
+        deposit_whitelist.clear();
+        draft_operators_whitelist.clear();
+
+        state
+    }
+}
+
+#[near(serializers=[borsh])]
+pub struct CheckpointV1 {
+    timestamp: TimestampSec,
+    balance: Balance,
+}
+
+#[near(serializers=[borsh])]
+pub struct ScheduleV1(Vec<CheckpointV1>);
+
+impl From<ScheduleV1> for Schedule {
+    fn from(schedule: ScheduleV1) -> Self {
+        Self::Checkpoints(
+            schedule
+                .0
+                .into_iter()
+                .map(|checkpoint| Checkpoint {
+                    timestamp: checkpoint.timestamp,
+                    balance: checkpoint.balance,
+                    interpolation: Interpolation::Linear,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[near(serializers=[borsh])]
+pub enum VestingConditionsV1 {
+    SameAsLockupSchedule,
+    Hash(Base58CryptoHash),
+    Schedule(ScheduleV1),
+}
+
+impl From<VestingConditionsV1> for VestingConditions {
+    fn from(vesting_conditions: VestingConditionsV1) -> Self {
+        match vesting_conditions {
+            VestingConditionsV1::SameAsLockupSchedule => Self::SameAsLockupSchedule,
+            // linear schedules keep their hashes
+            VestingConditionsV1::Hash(hash) => Self::Hash(hash),
+            VestingConditionsV1::Schedule(schedule) => Self::Schedule(schedule.into()),
+        }
+    }
+}
+
+#[near(serializers=[borsh])]
+pub struct TerminationConfigV1 {
+    beneficiary_id: AccountId,
+    vesting_schedule: VestingConditionsV1,
+}
+
+#[near(serializers=[borsh])]
+pub struct LockupV1 {
+    account_id: AccountId,
+    schedule: ScheduleV1,
+    claimed_balance: Balance,
+    termination_config: Option<TerminationConfigV1>,
+}
+
+impl From<LockupV1> for Lockup {
+    fn from(lockup: LockupV1) -> Self {
+        Self {
+            account_id: lockup.account_id,
+            schedule: lockup.schedule.into(),
+            claimed_balance: lockup.claimed_balance,
+            termination_config: lockup.termination_config.map(|config| TerminationConfig {
+                beneficiary_id: config.beneficiary_id,
+                vesting_schedule: config.vesting_schedule.into(),
+                terminator_id: None,
+            }),
+            auto_claim: false,
+            token: None,
+            pending_termination: None,
+            paused_at: None,
+            storage_payment: None,
+            pruned: false,
+        }
+    }
+}
+
+#[near(serializers=[borsh])]
+pub struct LockupCreateV1 {
+    account_id: AccountId,
+    schedule: ScheduleV1,
+    vesting_schedule: Option<VestingConditionsV1>,
+}
+
+#[near(serializers=[borsh])]
+pub struct DraftV1 {
+    draft_group_id: DraftGroupIndex,
+    lockup_create: LockupCreateV1,
+}
+
+impl From<DraftV1> for Draft {
+    fn from(draft: DraftV1) -> Self {
+        Self {
+            draft_group_id: draft.draft_group_id,
+            lockup_create: LockupCreate {
+                account_id: draft.lockup_create.account_id,
+                schedule: draft.lockup_create.schedule.into(),
+                vesting_schedule: draft.lockup_create.vesting_schedule.map(Into::into),
+                terminator_id: None,
+                refund_receiver_id: None,
+            },
+            storage_payment: None,
+        }
+    }
+}
+
+#[near(serializers=[borsh])]
+pub struct DraftGroupV1 {
+    total_amount: Balance,
+    payer_id: Option<AccountId>,
+    draft_indices: HashSet<DraftIndex>,
+    discarded: bool,
+}
+
+impl From<DraftGroupV1> for DraftGroup {
+    fn from(draft_group: DraftGroupV1) -> Self {
+        Self {
+            total_amount: draft_group.total_amount,
+            payer_id: draft_group.payer_id,
+            draft_indices: draft_group.draft_indices,
+            discarded: draft_group.discarded,
+            token_account_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::borsh;
+
+    use super::*;
+
+    fn schedule_v1() -> ScheduleV1 {
+        ScheduleV1(vec![
+            CheckpointV1 {
+                timestamp: 100,
+                balance: 0,
+            },
+            CheckpointV1 {
+                timestamp: 200,
+                balance: 1_000,
+            },
+        ])
+    }
+
+    fn linear_schedule() -> Schedule {
+        Schedule::Checkpoints(vec![
+            Checkpoint {
+                timestamp: 100,
+                balance: 0,
+                interpolation: Interpolation::Linear,
+            },
+            Checkpoint {
+                timestamp: 200,
+                balance: 1_000,
+                interpolation: Interpolation::Linear,
+            },
+        ])
+    }
+
+    #[test]
+    fn lockup_v1_is_migrated_to_checkpoint_schedule() {
+        let lockup = LockupV1 {
+            account_id: "alice.near".parse().unwrap(),
+            schedule: schedule_v1(),
+            claimed_balance: 300,
+            termination_config: Some(TerminationConfigV1 {
+                beneficiary_id: "owner.near".parse().unwrap(),
+                vesting_schedule: VestingConditionsV1::Schedule(schedule_v1()),
+            }),
+        };
+        // the old lockups are read from their stored bytes
+        let bytes = borsh::to_vec(&lockup).unwrap();
+        let lockup: Lockup = borsh::from_slice::<LockupV1>(&bytes).unwrap().into();
+
+        assert_eq!(linear_schedule(), lockup.schedule);
+        assert_eq!(300, lockup.claimed_balance);
+        let termination_config = lockup.termination_config.unwrap();
+        assert_eq!(
+            VestingConditions::Schedule(linear_schedule()),
+            termination_config.vesting_schedule
+        );
+        assert_eq!(None, termination_config.terminator_id);
+    }
+
+    #[test]
+    fn draft_v1_is_migrated_with_linear_interpolation() {
+        let draft = DraftV1 {
+            draft_group_id: 3,
+            lockup_create: LockupCreateV1 {
+                account_id: "alice.near".parse().unwrap(),
+                schedule: schedule_v1(),
+                vesting_schedule: Some(VestingConditionsV1::Schedule(schedule_v1())),
+            },
+        };
+        let bytes = borsh::to_vec(&draft).unwrap();
+        let draft: Draft = borsh::from_slice::<DraftV1>(&bytes).unwrap().into();
 
-        old_state
+        assert_eq!(3, draft.draft_group_id);
+        assert_eq!(linear_schedule(), draft.lockup_create.schedule);
+        assert_eq!(
+            Some(VestingConditions::Schedule(linear_schedule())),
+            draft.lockup_create.vesting_schedule
+        );
+        assert_eq!(None, draft.storage_payment);
     }
 }
@@ -0,0 +1,186 @@
+use hodl_model::{
+    lockup::{Lockup, LockupIndex},
+    storage::{StorageApi, StorageBalance, StorageBalanceBounds, StoragePayment},
+    Balance,
+};
+use near_sdk::{assert_one_yocto, env, json_types::U128, near_bindgen, AccountId, NearToken, Promise, StorageUsage};
+
+use crate::{Contract, ContractExt};
+
+/// The storage a lockup takes when it holds an unvested balance whose transfer has failed.
+/// It's reserved from the storage balance of the terminator until the transfer is done.
+pub(crate) const REFUND_LOCKUP_STORAGE: StorageUsage = 500;
+
+fn storage_cost(bytes: StorageUsage) -> Balance {
+    Balance::from(bytes) * env::storage_byte_cost().as_yoctonear()
+}
+
+impl Contract {
+    /// Takes the cost of `bytes` of storage from the available storage balance of the payer.
+    pub(crate) fn internal_charge_storage(&mut self, payer_id: &AccountId, bytes: StorageUsage) {
+        let mut balance = self
+            .storage_balances
+            .get(payer_id)
+            .expect("The payer has no storage balance");
+        let cost = storage_cost(bytes);
+        assert!(
+            balance.available.0 >= cost,
+            "Not enough storage balance, {cost} yoctoNEAR is required"
+        );
+        balance.available.0 -= cost;
+        self.storage_balances.insert(payer_id, &balance);
+    }
+
+    /// Takes the cost of `bytes` of storage if the payer's available storage balance covers it.
+    /// Returns the reserved bytes, which are zero otherwise.
+    pub(crate) fn internal_reserve_storage(&mut self, payer_id: &AccountId, bytes: StorageUsage) -> StorageUsage {
+        let Some(mut balance) = self.storage_balances.get(payer_id) else {
+            return 0;
+        };
+        let cost = storage_cost(bytes);
+        if balance.available.0 < cost {
+            return 0;
+        }
+        balance.available.0 -= cost;
+        self.storage_balances.insert(payer_id, &balance);
+
+        bytes
+    }
+
+    /// Returns the cost of reserved but unused `bytes` of storage to the payer.
+    pub(crate) fn internal_release_storage(&mut self, payer_id: &AccountId, bytes: StorageUsage) {
+        if bytes == 0 {
+            return;
+        }
+        // the reserve is lost if the payer has unregistered
+        if let Some(mut balance) = self.storage_balances.get(payer_id) {
+            balance.available.0 = (balance.available.0 + storage_cost(bytes)).min(balance.total.0);
+            self.storage_balances.insert(payer_id, &balance);
+        }
+    }
+
+    /// Refunds the storage freed since `initial_storage_usage` to the payer, but not more than it paid for.
+    pub(crate) fn internal_refund_storage(
+        &mut self,
+        payment: &mut StoragePayment,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let bytes = initial_storage_usage
+            .saturating_sub(env::storage_usage())
+            .min(payment.bytes);
+        if bytes == 0 {
+            return;
+        }
+        payment.bytes -= bytes;
+        self.internal_release_storage(&payment.payer_id, bytes);
+    }
+
+    /// Adds the lockup and charges the payer for the storage it takes.
+    pub(crate) fn internal_add_paid_lockup(&mut self, lockup: &mut Lockup, payer_id: &AccountId) -> LockupIndex {
+        let initial_storage_usage = env::storage_usage();
+        // the size of the payment doesn't depend on the paid bytes, so it's stored before they are known
+        lockup.storage_payment = Some(StoragePayment {
+            payer_id: payer_id.clone(),
+            bytes: 0,
+        });
+        let index = self.internal_add_lockup(lockup);
+        let bytes = env::storage_usage() - initial_storage_usage;
+        lockup.storage_payment = Some(StoragePayment {
+            payer_id: payer_id.clone(),
+            bytes,
+        });
+        self.lockups.replace(u64::from(index), lockup);
+        self.internal_charge_storage(payer_id, bytes);
+
+        index
+    }
+
+    /// Refunds the storage a finished lockup freed since `initial_storage_usage`.
+    pub(crate) fn internal_refund_lockup_storage(
+        &mut self,
+        index: LockupIndex,
+        lockup: &mut Lockup,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let Some(payment) = lockup.storage_payment.as_mut() else {
+            return;
+        };
+        self.internal_refund_storage(payment, initial_storage_usage);
+        self.lockups.replace(u64::from(index), lockup);
+    }
+}
+
+#[near_bindgen]
+impl StorageApi for Contract {
+    #[payable]
+    fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let amount = env::attached_deposit();
+        let mut balance = self.storage_balances.get(&account_id).unwrap_or_default();
+        if registration_only.unwrap_or(false) {
+            // the registration is free, so the deposit is returned
+            if !amount.is_zero() {
+                Promise::new(env::predecessor_account_id()).transfer(amount);
+            }
+        } else {
+            balance.total.0 += amount.as_yoctonear();
+            balance.available.0 += amount.as_yoctonear();
+        }
+        self.storage_balances.insert(&account_id, &balance);
+
+        balance
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut balance = self
+            .storage_balances
+            .get(&account_id)
+            .expect("The account has no storage balance");
+        let amount = amount.map_or(balance.available.0, |amount| amount.0);
+        assert!(
+            amount <= balance.available.0,
+            "The amount exceeds the available storage balance"
+        );
+        balance.total.0 -= amount;
+        balance.available.0 -= amount;
+        self.storage_balances.insert(&account_id, &balance);
+        if amount > 0 {
+            Promise::new(account_id).transfer(NearToken::from_yoctonear(amount));
+        }
+
+        balance
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let Some(balance) = self.storage_balances.get(&account_id) else {
+            return false;
+        };
+        assert!(
+            force.unwrap_or(false) || balance.available == balance.total,
+            "The account still pays for storage of lockups or drafts"
+        );
+        self.storage_balances.remove(&account_id);
+        if balance.available.0 > 0 {
+            Promise::new(account_id).transfer(NearToken::from_yoctonear(balance.available.0));
+        }
+
+        true
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: 0.into(),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_balances.get(&account_id)
+    }
+}
@@ -0,0 +1,130 @@
+use hodl_model::{
+    update::{ManagerApi, StagedUpdate, UpdateApi, UpdateTimelockApi},
+    util::current_timestamp_sec,
+    TimestampSec,
+};
+use near_sdk::{Gas, GasWeight, NearToken, Promise, PromiseOrValue};
+
+use crate::{
+    env,
+    event::{
+        emit, EventKind, FtLockupAcceptManager, FtLockupCancelUpdate, FtLockupProposeManager, FtLockupSetUpdateDelay,
+        FtLockupStageUpdate,
+    },
+    near_bindgen, AccountId, Base58CryptoHash, Contract, ContractExt,
+};
+
+#[near_bindgen]
+impl UpdateApi for Contract {
+    fn update_contract(&mut self) -> PromiseOrValue<()> {
+        self.assert_account_can_update();
+        // the code is the raw input, so it's not parsed as arguments
+        let code = env::input().expect("Expected the code");
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call_weight(
+                "migrate".to_string(),
+                vec![],
+                NearToken::from_yoctonear(0),
+                Gas::from_gas(0),
+                GasWeight(1),
+            )
+            .into()
+    }
+
+    fn set_multisig(&mut self, multisig: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.manager,
+            "Only the manager can set the multisig"
+        );
+        // the new manager still has to accept the role, so a mistyped account can't take over
+        self.propose_manager(Some(multisig));
+    }
+}
+
+#[near_bindgen]
+impl ManagerApi for Contract {
+    fn propose_manager(&mut self, manager: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.manager,
+            "Only the manager can propose a new manager"
+        );
+        self.proposed_manager.clone_from(&manager);
+        emit(EventKind::FtLockupProposeManager(FtLockupProposeManager { manager }));
+    }
+
+    fn accept_manager(&mut self) {
+        let account_id = env::predecessor_account_id();
+        assert_eq!(
+            self.proposed_manager.as_ref(),
+            Some(&account_id),
+            "Only the proposed manager can accept the role"
+        );
+        self.proposed_manager = None;
+        let old_manager = std::mem::replace(&mut self.manager, account_id.clone());
+        emit(EventKind::FtLockupAcceptManager(FtLockupAcceptManager {
+            manager: account_id,
+            old_manager,
+        }));
+    }
+
+    fn get_manager(&self) -> AccountId {
+        self.manager.clone()
+    }
+
+    fn get_proposed_manager(&self) -> Option<AccountId> {
+        self.proposed_manager.clone()
+    }
+}
+
+#[near_bindgen]
+impl UpdateTimelockApi for Contract {
+    fn stage_update(&mut self, code_hash: Base58CryptoHash) {
+        self.assert_updater(&env::predecessor_account_id());
+        let activation_timestamp = current_timestamp_sec()
+            .checked_add(self.update_delay)
+            .expect("The activation timestamp overflows");
+        self.staged_update = Some(StagedUpdate {
+            code_hash,
+            activation_timestamp,
+        });
+        emit(EventKind::FtLockupStageUpdate(FtLockupStageUpdate {
+            code_hash,
+            activation_timestamp,
+        }));
+    }
+
+    fn cancel_update(&mut self) {
+        self.assert_updater(&env::predecessor_account_id());
+        let staged_update = self.staged_update.take().expect("No staged update");
+        emit(EventKind::FtLockupCancelUpdate(FtLockupCancelUpdate {
+            code_hash: staged_update.code_hash,
+        }));
+    }
+
+    fn set_update_delay(&mut self, update_delay: TimestampSec) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.manager,
+            "Only the manager can set the update delay"
+        );
+        assert!(
+            update_delay >= self.update_delay,
+            "The update delay can only be increased"
+        );
+        self.update_delay = update_delay;
+        emit(EventKind::FtLockupSetUpdateDelay(FtLockupSetUpdateDelay {
+            update_delay,
+        }));
+    }
+
+    fn get_staged_update(&self) -> Option<StagedUpdate> {
+        self.staged_update.clone()
+    }
+
+    fn get_update_delay(&self) -> TimestampSec {
+        self.update_delay
+    }
+}
@@ -1,9 +1,12 @@
 use hodl_model::{
     draft::{DraftGroupIndex, DraftGroupView, DraftIndex, DraftView},
-    lockup::{LockupIndex, LockupView},
+    lockup::{ClaimRecord, LockupIndex, LockupToken, LockupView},
+    pause::PausableOperation,
+    preset::SchedulePreset,
+    role::Role,
     schedule::Schedule,
     view_api::LockupViewApi,
-    WrappedBalance,
+    TimestampSec, TokenAccountId, WrappedBalance,
 };
 
 use crate::{near_bindgen, AccountId, Base58CryptoHash, Contract, ContractExt, Into, VERSION};
@@ -14,14 +17,20 @@ impl LockupViewApi for Contract {
         self.token_account_id.clone()
     }
 
-    fn get_account_lockups(&self, account_id: AccountId) -> Vec<(LockupIndex, LockupView)> {
+    fn get_account_lockups(&self, account_id: AccountId, token: Option<LockupToken>) -> Vec<(LockupIndex, LockupView)> {
         self.internal_get_account_lockups(&account_id)
             .into_iter()
+            .filter(|(_, lockup)| {
+                token.as_ref().map_or(true, |token| {
+                    self.internal_lockup_token(lockup.token.as_ref()) == *token
+                })
+            })
             .map(|(lockup_index, lockup)| (lockup_index, lockup.into()))
             .collect()
     }
 
     fn get_lockup(&self, index: LockupIndex) -> Option<LockupView> {
+        self.assert_state_migrated();
         self.lockups.get(u64::from(index)).map(Into::into)
     }
 
@@ -33,6 +42,7 @@ impl LockupViewApi for Contract {
     }
 
     fn get_num_lockups(&self) -> u32 {
+        self.assert_state_migrated();
         self.lockups.len().try_into().unwrap()
     }
 
@@ -48,12 +58,61 @@ impl LockupViewApi for Contract {
             .collect()
     }
 
+    fn get_pending_lockup_transfer(&self, index: LockupIndex) -> Option<AccountId> {
+        self.lockup_transfers.get(&index)
+    }
+
+    fn get_claim_history(&self, index: LockupIndex, from_index: Option<u32>, limit: Option<u32>) -> Vec<ClaimRecord> {
+        self.claim_history
+            .get(&index)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.map_or(usize::MAX, |limit| limit as usize))
+            .collect()
+    }
+
+    fn get_claim_history_limit(&self) -> u32 {
+        self.claim_history_limit
+    }
+
     fn get_deposit_whitelist(&self) -> Vec<AccountId> {
-        self.deposit_whitelist.to_vec()
+        self.get_role_members(Role::LockupCreator)
     }
 
     fn get_draft_operators_whitelist(&self) -> Vec<AccountId> {
-        self.draft_operators_whitelist.to_vec()
+        self.get_role_members(Role::DraftOperator)
+    }
+
+    fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.role_members
+            .get(&role)
+            .map(|members| members.to_vec())
+            .unwrap_or_default()
+    }
+
+    fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        self.internal_has_role(&account_id, role)
+    }
+
+    fn get_relayers_whitelist(&self) -> Vec<AccountId> {
+        self.get_role_members(Role::Relayer)
+    }
+
+    fn get_max_relayer_fee_bps(&self) -> u16 {
+        self.max_relayer_fee_bps
+    }
+
+    fn get_token_whitelist(&self) -> Vec<TokenAccountId> {
+        self.token_whitelist.to_vec()
+    }
+
+    fn get_termination_grace_period(&self) -> TimestampSec {
+        self.termination_grace_period
+    }
+
+    fn get_paused_operations(&self) -> Vec<PausableOperation> {
+        self.paused_operations.iter().copied().collect()
     }
 
     fn hash_schedule(&self, schedule: Schedule) -> Base58CryptoHash {
@@ -73,6 +132,12 @@ impl LockupViewApi for Contract {
         }
     }
 
+    fn expand_schedule_preset(&self, preset: SchedulePreset, total_balance: WrappedBalance) -> Schedule {
+        let schedule = preset.build(total_balance.0);
+        schedule.assert_valid(total_balance.0);
+        schedule
+    }
+
     fn get_next_draft_group_id(&self) -> DraftGroupIndex {
         self.next_draft_group_id
     }
@@ -110,6 +175,7 @@ impl LockupViewApi for Contract {
     }
 
     fn get_draft(&self, index: DraftIndex) -> Option<DraftView> {
+        self.assert_state_migrated();
         self.drafts.get(&index as _).map(Into::into)
     }
 
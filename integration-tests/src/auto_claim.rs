@@ -0,0 +1,103 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage,
+    lockup::{LockupCreate, LockupIndex, LockupToken},
+    lockup_api::LockupApiIntegration,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{json, to_string};
+use near_workspaces::types::NearToken;
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn auto_claims_span_accounts_and_tokens() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    // alice has a lockup of the default token and a native one, bob has one of the default token
+    for account in [&alice, &bob] {
+        context
+            .ft_contract()
+            .storage_deposit(account.to_near().into(), None)
+            .call()
+            .await?;
+        let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(account.to_near(), 100));
+        context
+            .ft_contract()
+            .ft_transfer_call(
+                context.lockup().contract_account(),
+                100.into(),
+                None,
+                to_string(&message).unwrap(),
+            )
+            .with_user(&manager)
+            .call()
+            .await?;
+    }
+    let native_amount = NearToken::from_near(1);
+    manager
+        .call(context.lockup().contract().id(), "create_native_lockup")
+        .args_json(json!({
+            "lockup_create": LockupCreate::new_unlocked(alice.to_near(), native_amount.as_yoctonear()),
+        }))
+        .deposit(native_amount)
+        .transact()
+        .await?
+        .into_result()?;
+
+    context
+        .lockup()
+        .set_auto_claim(vec![0, 2], true)
+        .with_user(&alice)
+        .call()
+        .await?;
+    context
+        .lockup()
+        .set_auto_claim(vec![1], true)
+        .with_user(&bob)
+        .call()
+        .await?;
+
+    let next_index: LockupIndex = manager
+        .call(context.lockup().contract().id(), "process_auto_claims")
+        .args_json(json!({ "from_index": 0, "limit": 10 }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?
+        .json()?;
+    assert_eq!(3, next_index);
+
+    for index in 0..3 {
+        let lockup = context.lockup().get_lockup(index).call().await?.unwrap();
+        assert_eq!(lockup.total_balance, lockup.claimed_balance, "lockup #{index}");
+    }
+    let native_lockup = context.lockup().get_lockup(2).call().await?.unwrap();
+    assert_eq!(Some(LockupToken::Native), native_lockup.token);
+    // the claimed lockups are no longer indexed
+    assert!(context
+        .lockup()
+        .get_account_lockups(alice.to_near(), None)
+        .call()
+        .await?
+        .is_empty());
+    assert!(context
+        .lockup()
+        .get_account_lockups(bob.to_near(), None)
+        .call()
+        .await?
+        .is_empty());
+
+    Ok(())
+}
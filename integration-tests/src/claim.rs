@@ -0,0 +1,202 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage, lockup::LockupCreate, lockup_api::LockupApiIntegration, view_api::LockupViewApiIntegration,
+};
+use near_sdk::{
+    json_types::U128,
+    serde_json::{json, to_string, Value},
+};
+use near_workspaces::{types::NearToken, Account, Contract};
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::{GetContractAccount, LockupContract},
+    utils::load_wasm,
+};
+
+#[tokio::test]
+async fn claim_of_several_tokens_returns_total() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+
+    create_lockup(&mut context, &alice, 100).await?;
+    let native_amount = NearToken::from_near(1);
+    manager
+        .call(context.lockup().contract().id(), "create_native_lockup")
+        .args_json(json!({
+            "lockup_create": LockupCreate::new_unlocked(alice.to_near(), native_amount.as_yoctonear()),
+        }))
+        .deposit(native_amount)
+        .transact()
+        .await?
+        .into_result()?;
+
+    // alice isn't registered in the token yet, so only the native transfer succeeds
+    let claimed = context.lockup().claim(None).with_user(&alice).call().await?;
+    assert_eq!(native_amount.as_yoctonear(), claimed.0);
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(0, lockup.claimed_balance);
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    let claimed = context.lockup().claim(None).with_user(&alice).call().await?;
+    assert_eq!(100, claimed.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn claim_to_sends_tokens_to_receiver() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+    let carol = context.account("carol").await?;
+
+    create_lockup(&mut context, &alice, 100).await?;
+    context
+        .ft_contract()
+        .storage_deposit(bob.to_near().into(), None)
+        .call()
+        .await?;
+
+    let claimed = claim_to(&context, &alice, &bob, json!([[0, "30"]])).await?;
+    assert_eq!(30, claimed);
+    assert_eq!(30, context.ft_contract().ft_balance_of(bob.to_near()).call().await?.0);
+    assert_eq!(0, context.ft_contract().ft_balance_of(alice.to_near()).call().await?.0);
+
+    // carol isn't registered in the token, so the claim returns to the lockup
+    let claimed = claim_to(&context, &alice, &carol, json!(null)).await?;
+    assert_eq!(0, claimed);
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(30, lockup.claimed_balance);
+
+    let claimed = claim_to(&context, &alice, &bob, json!(null)).await?;
+    assert_eq!(70, claimed);
+    assert_eq!(100, context.ft_contract().ft_balance_of(bob.to_near()).call().await?.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn claim_and_call_refunds_failed_call() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    create_lockup(&mut context, &alice, 100).await?;
+
+    // another lockup contract receives the claimed tokens, the lockup contract creates lockups there
+    let receiver = context
+        .worker
+        .dev_deploy(&load_wasm("../res/hodl_lockup.wasm")?)
+        .await?;
+    receiver
+        .call("new")
+        .args_json(json!({
+            "token_account_id": context.ft_contract().contract_account(),
+            "deposit_whitelist": [context.lockup().contract_account()],
+            "draft_operators_whitelist": null,
+            "manager": receiver.id(),
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    context
+        .ft_contract()
+        .storage_deposit(receiver.as_account().to_near().into(), None)
+        .call()
+        .await?;
+    alice
+        .call(receiver.id(), "storage_deposit")
+        .args_json(json!({ "account_id": context.lockup().contract_account() }))
+        .deposit(NearToken::from_millinear(50))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // the receiver can't parse the message, so the token refunds the whole amount
+    let claimed = claim_and_call(&context, &alice, &receiver, "not a lockup").await?;
+    assert_eq!(0, claimed);
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(0, lockup.claimed_balance);
+    assert!(!context
+        .lockup()
+        .get_account_lockups(alice.to_near(), None)
+        .call()
+        .await?
+        .is_empty());
+
+    // the claimed tokens are locked up for bob in the receiver
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(bob.to_near(), 100));
+    let claimed = claim_and_call(&context, &alice, &receiver, &to_string(&message).unwrap()).await?;
+    assert_eq!(100, claimed);
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(100, lockup.claimed_balance);
+    let lockup = LockupContract::with_contract(&receiver)
+        .get_lockup(0)
+        .call()
+        .await?
+        .unwrap();
+    assert_eq!(bob.to_near(), lockup.account_id);
+    assert_eq!(100, lockup.total_balance);
+
+    Ok(())
+}
+
+async fn claim_to(context: &Context, account: &Account, receiver: &Account, amounts: Value) -> Result<u128> {
+    let claimed: U128 = account
+        .call(context.lockup().contract().id(), "claim_to")
+        .args_json(json!({ "receiver_id": receiver.id(), "amounts": amounts }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?
+        .json()?;
+
+    Ok(claimed.0)
+}
+
+async fn claim_and_call(context: &Context, account: &Account, receiver: &Contract, msg: &str) -> Result<u128> {
+    let claimed: U128 = account
+        .call(context.lockup().contract().id(), "claim_and_call")
+        .args_json(json!({ "receiver_id": receiver.id(), "msg": msg }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?
+        .json()?;
+
+    Ok(claimed.0)
+}
+
+/// Creates an unlocked lockup of the default token for the account.
+async fn create_lockup(context: &mut Context, account: &Account, amount: u128) -> Result<()> {
+    let manager = context.manager().await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(account.to_near(), amount));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            amount.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage, lockup::LockupCreate, lockup_api::LockupApiIntegration, storage::StorageApiIntegration,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{json, to_string};
+use near_workspaces::{types::NearToken, Account};
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn claim_history_keeps_latest_claims() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+
+    assert_eq!(0, context.lockup().get_claim_history_limit().call().await?);
+    manager
+        .call(context.lockup().contract().id(), "set_claim_history_limit")
+        .args_json(json!({ "limit": 2 }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(2, context.lockup().get_claim_history_limit().call().await?);
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(alice.to_near(), 100));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            100.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    let initial_balance = storage_balance(&context, &manager).await?;
+    for _ in 0..2 {
+        context
+            .lockup()
+            .claim(Some(vec![(0, Some(30.into()))]))
+            .with_user(&alice)
+            .call()
+            .await?;
+    }
+    // the creator of the lockup pays for its history
+    assert!(storage_balance(&context, &manager).await? < initial_balance);
+    context.lockup().claim(None).with_user(&alice).call().await?;
+
+    // only the latest claims are kept
+    let history = context.lockup().get_claim_history(0, None, None).call().await?;
+    assert_eq!(
+        vec![30, 40],
+        history.iter().map(|record| record.amount.0).collect::<Vec<_>>()
+    );
+    assert!(history.iter().all(|record| record.receiver_id == alice.to_near()));
+    assert!(history[0].timestamp <= history[1].timestamp);
+
+    let page = context.lockup().get_claim_history(0, Some(1), Some(1)).call().await?;
+    assert_eq!(vec![history[1].clone()], page);
+
+    // the history outlives the pruned lockup
+    assert_eq!(vec![0], context.lockup().prune_lockups(vec![0]).call().await?);
+    assert_eq!(history, context.lockup().get_claim_history(0, None, None).call().await?);
+
+    Ok(())
+}
+
+async fn storage_balance(context: &Context, account: &Account) -> Result<u128> {
+    let balance = context
+        .lockup()
+        .storage_balance_of(account.to_near())
+        .call()
+        .await?
+        .expect("The account has no storage balance");
+    Ok(balance.available.0)
+}
@@ -118,6 +118,14 @@ pub(crate) async fn prepare_contract() -> Result<Context> {
         .call()
         .await?;
 
+    // the manager pays for storage of the lockups it creates
+    manager
+        .call(context.lockup().contract().id(), "storage_deposit")
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await?
+        .into_result()?;
+
     context.helper().new().result().await?;
 
     Ok(context)
@@ -1,7 +1,19 @@
+mod auto_claim;
+mod claim;
+mod claim_history;
 pub mod context;
 mod helper_test;
 mod lockup_interface;
 mod migrate_to_multisig;
 mod migration;
+mod native;
+mod pause;
+mod prune;
+mod relayer;
+mod roles;
+mod split;
+mod storage;
+mod termination;
+mod transfer;
 mod update;
 mod utils;
@@ -3,14 +3,26 @@
 use integration_utils::{contract_call::ContractCall, integration_contract::IntegrationContract, misc::ToNear};
 use model::{
     draft::{Draft, DraftGroupIndex, DraftGroupView, DraftIndex, DraftView},
-    lockup::{LockupIndex, LockupView},
+    lockup::{ClaimRecord, LockupCreate, LockupIndex, LockupToken, LockupView},
     lockup_api::LockupApiIntegration,
+    pause::PausableOperation,
+    preset::SchedulePreset,
+    role::Role,
     schedule::Schedule,
-    update::UpdateApiIntegration,
+    storage::{StorageApiIntegration, StorageBalance, StorageBalanceBounds},
+    termination::LockupReduction,
+    update::{
+        ManagerApiIntegration, MigrationApiIntegration, StagedUpdate, UpdateApiIntegration,
+        UpdateTimelockApiIntegration,
+    },
     view_api::LockupViewApiIntegration,
-    TimestampSec, WrappedBalance,
+    TimestampSec, TokenAccountId, WrappedBalance,
+};
+use near_sdk::{
+    json_types::{Base58CryptoHash, U128},
+    serde_json::json,
+    AccountId,
 };
-use near_sdk::{json_types::Base58CryptoHash, serde_json::json, AccountId};
 use near_workspaces::Contract;
 
 pub struct LockupContract<'a> {
@@ -35,6 +47,14 @@ impl LockupApiIntegration for LockupContract<'_> {
             .unwrap()
     }
 
+    fn create_native_lockup(&mut self, lockup_create: LockupCreate) -> ContractCall<LockupIndex> {
+        self.make_call("create_native_lockup")
+            .args_json(json!({
+                "lockup_create": lockup_create
+            }))
+            .unwrap()
+    }
+
     fn claim(&mut self, amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>) -> ContractCall<WrappedBalance> {
         self.make_call("claim")
             .args_json(json!({
@@ -43,6 +63,67 @@ impl LockupApiIntegration for LockupContract<'_> {
             .unwrap()
     }
 
+    fn claim_to(
+        &mut self,
+        receiver_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> ContractCall<WrappedBalance> {
+        self.make_call("claim_to")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "amounts": amounts
+            }))
+            .unwrap()
+    }
+
+    fn claim_and_call(
+        &mut self,
+        receiver_id: AccountId,
+        msg: String,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> ContractCall<WrappedBalance> {
+        self.make_call("claim_and_call")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "msg": msg,
+                "amounts": amounts
+            }))
+            .unwrap()
+    }
+
+    fn claim_for(
+        &mut self,
+        account_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+        fee: Option<WrappedBalance>,
+    ) -> ContractCall<WrappedBalance> {
+        self.make_call("claim_for")
+            .args_json(json!({
+                "account_id": account_id,
+                "amounts": amounts,
+                "fee": fee
+            }))
+            .unwrap()
+    }
+
+    fn set_auto_claim(&mut self, lockup_indices: Vec<LockupIndex>, auto_claim: bool) -> ContractCall<()> {
+        self.make_call("set_auto_claim")
+            .args_json(json!({
+                "lockup_indices": lockup_indices,
+                "auto_claim": auto_claim,
+            }))
+            .unwrap()
+    }
+
+    fn process_auto_claims(&mut self, from_index: LockupIndex, limit: LockupIndex) -> ContractCall<LockupIndex> {
+        self.make_call("process_auto_claims")
+            .args_json(json!({
+                "from_index": from_index,
+                "limit": limit,
+            }))
+            .unwrap()
+    }
+
     fn terminate(
         &mut self,
         lockup_index: LockupIndex,
@@ -58,6 +139,165 @@ impl LockupApiIntegration for LockupContract<'_> {
             .unwrap()
     }
 
+    fn schedule_termination(
+        &mut self,
+        lockup_index: LockupIndex,
+        termination_timestamp: Option<TimestampSec>,
+    ) -> ContractCall<()> {
+        self.make_call("schedule_termination")
+            .args_json(json!({
+                "lockup_index": lockup_index,
+                "termination_timestamp": termination_timestamp
+            }))
+            .unwrap()
+    }
+
+    fn cancel_termination(&mut self, lockup_index: LockupIndex) -> ContractCall<()> {
+        self.make_call("cancel_termination")
+            .args_json(json!({
+                "lockup_index": lockup_index
+            }))
+            .unwrap()
+    }
+
+    fn execute_termination(
+        &mut self,
+        lockup_index: LockupIndex,
+        hashed_schedule: Option<Schedule>,
+    ) -> ContractCall<WrappedBalance> {
+        self.make_call("execute_termination")
+            .args_json(json!({
+                "lockup_index": lockup_index,
+                "hashed_schedule": hashed_schedule
+            }))
+            .unwrap()
+    }
+
+    fn reduce_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        reduction: LockupReduction,
+        hashed_schedule: Option<Schedule>,
+    ) -> ContractCall<WrappedBalance> {
+        self.make_call("reduce_lockup")
+            .args_json(json!({
+                "lockup_index": lockup_index,
+                "reduction": reduction,
+                "hashed_schedule": hashed_schedule
+            }))
+            .unwrap()
+    }
+
+    fn pause_lockup(&mut self, lockup_index: LockupIndex) -> ContractCall<()> {
+        self.make_call("pause_lockup")
+            .args_json(json!({
+                "lockup_index": lockup_index
+            }))
+            .unwrap()
+    }
+
+    fn resume_lockup(&mut self, lockup_index: LockupIndex, hashed_schedule: Option<Schedule>) -> ContractCall<()> {
+        self.make_call("resume_lockup")
+            .args_json(json!({
+                "lockup_index": lockup_index,
+                "hashed_schedule": hashed_schedule
+            }))
+            .unwrap()
+    }
+
+    fn set_termination_grace_period(&mut self, grace_period: TimestampSec) -> ContractCall<()> {
+        self.make_call("set_termination_grace_period")
+            .args_json(json!({
+                "grace_period": grace_period
+            }))
+            .unwrap()
+    }
+
+    fn set_claim_history_limit(&mut self, limit: u32) -> ContractCall<()> {
+        self.make_call("set_claim_history_limit")
+            .args_json(json!({
+                "limit": limit
+            }))
+            .unwrap()
+    }
+
+    fn transfer_lockup(&mut self, lockup_index: LockupIndex, receiver_id: AccountId) -> ContractCall<()> {
+        self.make_call("transfer_lockup")
+            .args_json(json!({
+                "lockup_index": lockup_index,
+                "receiver_id": receiver_id,
+            }))
+            .unwrap()
+    }
+
+    fn approve_lockup_transfer(&mut self, lockup_index: LockupIndex) -> ContractCall<()> {
+        self.make_call("approve_lockup_transfer")
+            .args_json(json!({
+                "lockup_index": lockup_index
+            }))
+            .unwrap()
+    }
+
+    fn cancel_lockup_transfer(&mut self, lockup_index: LockupIndex) -> ContractCall<()> {
+        self.make_call("cancel_lockup_transfer")
+            .args_json(json!({
+                "lockup_index": lockup_index
+            }))
+            .unwrap()
+    }
+
+    fn split_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        portions: Vec<(AccountId, WrappedBalance)>,
+    ) -> ContractCall<Vec<LockupIndex>> {
+        self.make_call("split_lockup")
+            .args_json(json!({
+                "lockup_index": lockup_index,
+                "portions": portions,
+            }))
+            .unwrap()
+    }
+
+    fn merge_lockups(
+        &mut self,
+        lockup_indices: Option<Vec<LockupIndex>>,
+        token: Option<LockupToken>,
+    ) -> ContractCall<LockupIndex> {
+        self.make_call("merge_lockups")
+            .args_json(json!({
+                "lockup_indices": lockup_indices,
+                "token": token,
+            }))
+            .unwrap()
+    }
+
+    fn prune_lockups(&mut self, lockup_indices: Vec<LockupIndex>) -> ContractCall<Vec<LockupIndex>> {
+        self.make_call("prune_lockups")
+            .args_json(json!({
+                "lockup_indices": lockup_indices
+            }))
+            .unwrap()
+    }
+
+    fn grant_role(&mut self, role: Role, account_ids: Vec<AccountId>) -> ContractCall<()> {
+        self.make_call("grant_role")
+            .args_json(json!({
+                "role": role,
+                "account_ids": account_ids
+            }))
+            .unwrap()
+    }
+
+    fn revoke_role(&mut self, role: Role, account_ids: Vec<AccountId>) -> ContractCall<()> {
+        self.make_call("revoke_role")
+            .args_json(json!({
+                "role": role,
+                "account_ids": account_ids
+            }))
+            .unwrap()
+    }
+
     fn add_to_deposit_whitelist(
         &mut self,
         account_id: Option<AccountId>,
@@ -100,6 +340,62 @@ impl LockupApiIntegration for LockupContract<'_> {
             .unwrap()
     }
 
+    fn add_to_relayers_whitelist(&mut self, account_ids: Vec<AccountId>) -> ContractCall<()> {
+        self.make_call("add_to_relayers_whitelist")
+            .args_json(json!({
+                "account_ids": account_ids
+            }))
+            .unwrap()
+    }
+
+    fn remove_from_relayers_whitelist(&mut self, account_ids: Vec<AccountId>) -> ContractCall<()> {
+        self.make_call("remove_from_relayers_whitelist")
+            .args_json(json!({
+                "account_ids": account_ids
+            }))
+            .unwrap()
+    }
+
+    fn set_max_relayer_fee_bps(&mut self, max_fee_bps: u16) -> ContractCall<()> {
+        self.make_call("set_max_relayer_fee_bps")
+            .args_json(json!({
+                "max_fee_bps": max_fee_bps
+            }))
+            .unwrap()
+    }
+
+    fn add_to_token_whitelist(&mut self, token_account_ids: Vec<TokenAccountId>) -> ContractCall<()> {
+        self.make_call("add_to_token_whitelist")
+            .args_json(json!({
+                "token_account_ids": token_account_ids
+            }))
+            .unwrap()
+    }
+
+    fn remove_from_token_whitelist(&mut self, token_account_ids: Vec<TokenAccountId>) -> ContractCall<()> {
+        self.make_call("remove_from_token_whitelist")
+            .args_json(json!({
+                "token_account_ids": token_account_ids
+            }))
+            .unwrap()
+    }
+
+    fn pause(&mut self, operations: Vec<PausableOperation>) -> ContractCall<()> {
+        self.make_call("pause")
+            .args_json(json!({
+                "operations": operations
+            }))
+            .unwrap()
+    }
+
+    fn unpause(&mut self, operations: Vec<PausableOperation>) -> ContractCall<()> {
+        self.make_call("unpause")
+            .args_json(json!({
+                "operations": operations
+            }))
+            .unwrap()
+    }
+
     fn create_draft_group(&mut self) -> ContractCall<DraftGroupIndex> {
         self.make_call("create_draft_group")
     }
@@ -150,10 +446,15 @@ impl<'a> LockupViewApiIntegration for LockupContract<'a> {
         self.make_call("get_token_account_id")
     }
 
-    fn get_account_lockups(&self, account_id: AccountId) -> ContractCall<Vec<(LockupIndex, LockupView)>> {
+    fn get_account_lockups(
+        &self,
+        account_id: AccountId,
+        token: Option<LockupToken>,
+    ) -> ContractCall<Vec<(LockupIndex, LockupView)>> {
         self.make_call("get_account_lockups")
             .args_json(json!({
-                "account_id": account_id
+                "account_id": account_id,
+                "token": token,
             }))
             .unwrap()
     }
@@ -191,6 +492,33 @@ impl<'a> LockupViewApiIntegration for LockupContract<'a> {
             .unwrap()
     }
 
+    fn get_pending_lockup_transfer(&self, index: LockupIndex) -> ContractCall<Option<AccountId>> {
+        self.make_call("get_pending_lockup_transfer")
+            .args_json(json!({
+                "index": index
+            }))
+            .unwrap()
+    }
+
+    fn get_claim_history(
+        &self,
+        index: LockupIndex,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> ContractCall<Vec<ClaimRecord>> {
+        self.make_call("get_claim_history")
+            .args_json(json!({
+                "index": index,
+                "from_index": from_index,
+                "limit": limit
+            }))
+            .unwrap()
+    }
+
+    fn get_claim_history_limit(&self) -> ContractCall<u32> {
+        self.make_call("get_claim_history_limit")
+    }
+
     fn get_deposit_whitelist(&self) -> ContractCall<Vec<AccountId>> {
         self.make_call("get_deposit_whitelist")
     }
@@ -199,6 +527,43 @@ impl<'a> LockupViewApiIntegration for LockupContract<'a> {
         self.make_call("get_draft_operators_whitelist")
     }
 
+    fn get_role_members(&self, role: Role) -> ContractCall<Vec<AccountId>> {
+        self.make_call("get_role_members")
+            .args_json(json!({
+                "role": role
+            }))
+            .unwrap()
+    }
+
+    fn has_role(&self, account_id: AccountId, role: Role) -> ContractCall<bool> {
+        self.make_call("has_role")
+            .args_json(json!({
+                "account_id": account_id,
+                "role": role
+            }))
+            .unwrap()
+    }
+
+    fn get_relayers_whitelist(&self) -> ContractCall<Vec<AccountId>> {
+        self.make_call("get_relayers_whitelist")
+    }
+
+    fn get_max_relayer_fee_bps(&self) -> ContractCall<u16> {
+        self.make_call("get_max_relayer_fee_bps")
+    }
+
+    fn get_token_whitelist(&self) -> ContractCall<Vec<TokenAccountId>> {
+        self.make_call("get_token_whitelist")
+    }
+
+    fn get_termination_grace_period(&self) -> ContractCall<TimestampSec> {
+        self.make_call("get_termination_grace_period")
+    }
+
+    fn get_paused_operations(&self) -> ContractCall<Vec<PausableOperation>> {
+        self.make_call("get_paused_operations")
+    }
+
     fn hash_schedule(&self, schedule: Schedule) -> ContractCall<Base58CryptoHash> {
         self.make_call("hash_schedule")
             .args_json(json!({
@@ -222,6 +587,15 @@ impl<'a> LockupViewApiIntegration for LockupContract<'a> {
             .unwrap()
     }
 
+    fn expand_schedule_preset(&self, preset: SchedulePreset, total_balance: WrappedBalance) -> ContractCall<Schedule> {
+        self.make_call("expand_schedule_preset")
+            .args_json(json!({
+                "preset": preset,
+                "total_balance": total_balance,
+            }))
+            .unwrap()
+    }
+
     fn get_next_draft_group_id(&self) -> ContractCall<DraftGroupIndex> {
         self.make_call("get_next_draft_group_id")
     }
@@ -290,6 +664,115 @@ impl<'a> UpdateApiIntegration for LockupContract<'a> {
     }
 }
 
+impl<'a> ManagerApiIntegration for LockupContract<'a> {
+    fn propose_manager(&mut self, manager: Option<AccountId>) -> ContractCall<()> {
+        self.make_call("propose_manager")
+            .args_json(json!({
+                "manager": manager
+            }))
+            .unwrap()
+    }
+
+    fn accept_manager(&mut self) -> ContractCall<()> {
+        self.make_call("accept_manager")
+    }
+
+    fn get_manager(&self) -> ContractCall<AccountId> {
+        self.make_call("get_manager")
+    }
+
+    fn get_proposed_manager(&self) -> ContractCall<Option<AccountId>> {
+        self.make_call("get_proposed_manager")
+    }
+}
+
+impl<'a> UpdateTimelockApiIntegration for LockupContract<'a> {
+    fn stage_update(&mut self, code_hash: Base58CryptoHash) -> ContractCall<()> {
+        self.make_call("stage_update")
+            .args_json(json!({
+                "code_hash": code_hash
+            }))
+            .unwrap()
+    }
+
+    fn cancel_update(&mut self) -> ContractCall<()> {
+        self.make_call("cancel_update")
+    }
+
+    fn set_update_delay(&mut self, update_delay: TimestampSec) -> ContractCall<()> {
+        self.make_call("set_update_delay")
+            .args_json(json!({
+                "update_delay": update_delay
+            }))
+            .unwrap()
+    }
+
+    fn get_staged_update(&self) -> ContractCall<Option<StagedUpdate>> {
+        self.make_call("get_staged_update")
+    }
+
+    fn get_update_delay(&self) -> ContractCall<TimestampSec> {
+        self.make_call("get_update_delay")
+    }
+}
+
+impl<'a> MigrationApiIntegration for LockupContract<'a> {
+    fn migrate_state(&mut self, limit: u32) -> ContractCall<bool> {
+        self.make_call("migrate_state")
+            .args_json(json!({
+                "limit": limit
+            }))
+            .unwrap()
+    }
+
+    fn is_state_migrated(&self) -> ContractCall<bool> {
+        self.make_call("is_state_migrated")
+    }
+}
+
+impl<'a> StorageApiIntegration for LockupContract<'a> {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> ContractCall<StorageBalance> {
+        self.make_call("storage_deposit")
+            .args_json(json!({
+                "account_id": account_id,
+                "registration_only": registration_only
+            }))
+            .unwrap()
+    }
+
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> ContractCall<StorageBalance> {
+        self.make_call("storage_withdraw")
+            .args_json(json!({
+                "amount": amount
+            }))
+            .unwrap()
+    }
+
+    fn storage_unregister(&mut self, force: Option<bool>) -> ContractCall<bool> {
+        self.make_call("storage_unregister")
+            .args_json(json!({
+                "force": force
+            }))
+            .unwrap()
+    }
+
+    fn storage_balance_bounds(&self) -> ContractCall<StorageBalanceBounds> {
+        self.make_call("storage_balance_bounds")
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> ContractCall<Option<StorageBalance>> {
+        self.make_call("storage_balance_of")
+            .args_json(json!({
+                "account_id": account_id
+            }))
+            .unwrap()
+    }
+}
+
 impl<'a> IntegrationContract<'a> for LockupContract<'a> {
     fn with_contract(contract: &'a Contract) -> Self {
         Self { contract }
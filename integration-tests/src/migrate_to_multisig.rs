@@ -97,6 +97,8 @@ async fn create_lockups(context: &mut Context) -> anyhow::Result<()> {
             account_id: account.to_near(),
             schedule: Schedule::new_unlocked(100),
             vesting_schedule: None,
+            terminator_id: None,
+            refund_receiver_id: None,
         });
 
         context
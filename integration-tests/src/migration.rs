@@ -1,37 +1,218 @@
 #![cfg(test)]
 
 use anyhow::Result;
-use integration_utils::misc::ToNear;
-use model::{ft_message::FtMessage, lockup::LockupCreate, schedule::Schedule, view_api::LockupViewApiIntegration};
-use near_sdk::serde_json::to_string;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{update::MigrationApiIntegration, view_api::LockupViewApiIntegration};
+use near_sdk::serde_json::json;
+use near_workspaces::{operations::Function, types::Gas, Contract};
 use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration, SweatApiIntegration};
 
 use crate::{
     context::{prepare_contract, Context, IntegrationContext},
-    lockup_interface::GetContractAccount,
+    lockup_interface::LockupContract,
+    utils::load_wasm,
 };
 
+const LOCKUPS_COUNT: u32 = 20;
+
 #[tokio::test]
 async fn migration() -> Result<()> {
     let mut context = prepare_contract().await?;
 
-    create_lockups(&mut context).await?;
+    let old_contract = deploy_old_contract(&mut context).await?;
+    create_lockups(&mut context, &old_contract).await?;
+
+    old_contract
+        .as_account()
+        .deploy(&load_wasm("../res/hodl_lockup.wasm")?)
+        .await?
+        .into_result()?;
+    old_contract.call("migrate").transact().await?.into_result()?;
+
+    let mut lockup = LockupContract::with_contract(&old_contract);
+
+    assert!(!lockup.is_state_migrated().call().await?);
+
+    let mut pages = 0;
+    while !lockup
+        .migrate_state(7)
+        .with_user(old_contract.as_account())
+        .call()
+        .await?
+    {
+        pages += 1;
+    }
+
+    assert!(pages > 1);
+    assert!(lockup.is_state_migrated().call().await?);
+    assert_eq!(LOCKUPS_COUNT, lockup.get_num_lockups().call().await?);
+
+    for index in 0..LOCKUPS_COUNT {
+        let account = context.account(&format!("bob_{index}")).await?;
+        let view = lockup.get_lockup(index).call().await?.expect("The lockup is lost");
+
+        assert_eq!(account.to_near(), view.account_id);
+        assert_eq!(100, view.total_balance);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lockups_are_unavailable_during_migration() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let old_contract = deploy_old_contract(&mut context).await?;
+    create_lockups(&mut context, &old_contract).await?;
+
+    old_contract
+        .as_account()
+        .deploy(&load_wasm("../res/hodl_lockup.wasm")?)
+        .await?
+        .into_result()?;
+    old_contract.call("migrate").transact().await?.into_result()?;
 
-    dbg!(context.lockup().get_num_lockups().call().await?);
+    let mut lockup = LockupContract::with_contract(&old_contract);
+    let bob = context.account("bob_0").await?;
+
+    // the first lockups are moved, but the account index isn't consistent until the migration is complete
+    assert!(
+        !lockup
+            .migrate_state(LOCKUPS_COUNT / 2)
+            .with_user(old_contract.as_account())
+            .call()
+            .await?
+    );
+
+    let Err(error) = lockup.claim(None).with_user(&bob).call().await else {
+        panic!("The lockups can't be claimed during the migration");
+    };
+    assert!(error.to_string().contains("The state is not migrated yet"));
+    assert!(lockup.get_account_lockups(bob.to_near(), None).call().await.is_err());
+
+    while !lockup
+        .migrate_state(LOCKUPS_COUNT)
+        .with_user(old_contract.as_account())
+        .call()
+        .await?
+    {}
+
+    let claimed = lockup.claim(None).with_user(&bob).call().await?;
+    assert_eq!(100, claimed.0);
 
     Ok(())
 }
 
-async fn create_lockups(context: &mut Context) -> Result<()> {
+#[tokio::test]
+async fn claim_in_flight_is_rolled_back_during_migration() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let old_contract = deploy_old_contract(&mut context).await?;
+    create_lockups(&mut context, &old_contract).await?;
+
+    // carol isn't registered in the token, so her claim fails
     let manager = context.manager().await?;
+    let carol = context.account("carol").await?;
+    let message = json!({
+        "account_id": carol.to_near(),
+        "schedule": [
+            { "timestamp": 0, "balance": "0" },
+            { "timestamp": 1, "balance": "100" },
+        ],
+    });
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            old_contract.as_account().to_near(),
+            100.into(),
+            None,
+            message.to_string(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    let claim = carol
+        .call(old_contract.id(), "claim")
+        .args_json(json!({}))
+        .max_gas()
+        .transact_async()
+        .await?;
+
+    // the callback of the claim comes blocks later, when only the first lockup is moved
+    old_contract
+        .as_account()
+        .batch(old_contract.id())
+        .deploy(&load_wasm("../res/hodl_lockup.wasm")?)
+        .call(Function::new("migrate").gas(Gas::from_tgas(100)))
+        .call(
+            Function::new("migrate_state")
+                .args_json(json!({ "limit": 1 }))
+                .gas(Gas::from_tgas(100)),
+        )
+        .transact()
+        .await?
+        .into_result()?;
+    claim.wait().await?;
+
+    let mut lockup = LockupContract::with_contract(&old_contract);
+    assert!(!lockup.is_state_migrated().call().await?);
+    while !lockup
+        .migrate_state(LOCKUPS_COUNT)
+        .with_user(old_contract.as_account())
+        .call()
+        .await?
+    {}
+
+    let view = lockup
+        .get_lockup(LOCKUPS_COUNT)
+        .call()
+        .await?
+        .expect("The lockup is lost");
+    assert_eq!(carol.to_near(), view.account_id);
+    assert_eq!(0, view.claimed_balance);
+    let account_lockups = lockup.get_account_lockups(carol.to_near(), None).call().await?;
+    assert_eq!(
+        vec![LOCKUPS_COUNT],
+        account_lockups.into_iter().map(|(index, _)| index).collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+/// Deploys the 1.2.0 version that is managed by its own account, so it can update itself.
+async fn deploy_old_contract(context: &mut Context) -> Result<Contract> {
+    let manager = context.manager().await?;
+    let contract = context
+        .worker
+        .dev_deploy(&load_wasm("../res/hodl_lockup_1.2.0.wasm")?)
+        .await?;
+
+    contract
+        .call("new")
+        .args_json(json!({
+            "token_account_id": context.ft_contract().contract_account(),
+            "deposit_whitelist": [manager.to_near()],
+            "draft_operators_whitelist": [],
+            "manager": contract.id(),
+        }))
+        .transact()
+        .await?
+        .into_result()?;
 
     context
         .ft_contract()
-        .tge_mint(&manager.to_near(), 1_000_000_000.into())
+        .storage_deposit(contract.as_account().to_near().into(), None)
         .call()
         .await?;
 
-    for i in 0..20 {
+    Ok(contract)
+}
+
+async fn create_lockups(context: &mut Context, contract: &Contract) -> Result<()> {
+    let manager = context.manager().await?;
+
+    for i in 0..LOCKUPS_COUNT {
         let account = context.account(&format!("bob_{i}")).await?;
 
         context
@@ -40,20 +221,18 @@ async fn create_lockups(context: &mut Context) -> Result<()> {
             .call()
             .await?;
 
-        let message = FtMessage::LockupCreate(LockupCreate {
-            account_id: account.to_near(),
-            schedule: Schedule::new_unlocked(100),
-            vesting_schedule: None,
+        // the 1.2.0 schedule is a plain list of checkpoints
+        let message = json!({
+            "account_id": account.to_near(),
+            "schedule": [
+                { "timestamp": 0, "balance": "0" },
+                { "timestamp": 1, "balance": "100" },
+            ],
         });
 
         context
             .ft_contract()
-            .ft_transfer_call(
-                context.lockup().contract_account(),
-                100.into(),
-                None,
-                to_string(&message).unwrap(),
-            )
+            .ft_transfer_call(contract.as_account().to_near(), 100.into(), None, message.to_string())
             .with_user(&manager)
             .call()
             .await?;
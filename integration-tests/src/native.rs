@@ -0,0 +1,95 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    lockup::{LockupCreate, LockupToken},
+    lockup_api::LockupApiIntegration,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{json, Value};
+use near_workspaces::{result::ExecutionFinalResult, types::NearToken, Account};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn native_lockups_are_claimed_and_terminated_in_near() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let carol = context.account("carol").await?;
+
+    let amount = NearToken::from_near(1);
+    let unlocked = json!(LockupCreate::new_unlocked(alice.to_near(), amount.as_yoctonear()));
+    let result = create_native_lockup(&context, &manager, unlocked.clone(), NearToken::from_millinear(500)).await?;
+    assert!(result.into_result().is_err(), "The deposit should match the balance");
+    create_native_lockup(&context, &manager, unlocked, amount)
+        .await?
+        .into_result()?;
+
+    // the balance vests in the far future, so all of it is refunded on termination
+    let terminable = json!({
+        "account_id": alice.to_near(),
+        "schedule": [
+            { "timestamp": 4_000_000_000u64, "balance": "0" },
+            { "timestamp": 4_100_000_000u64, "balance": amount.as_yoctonear().to_string() },
+        ],
+        "vesting_schedule": "SameAsLockupSchedule",
+        "refund_receiver_id": carol.to_near(),
+    });
+    create_native_lockup(&context, &manager, terminable, amount)
+        .await?
+        .into_result()?;
+    let lockup = context.lockup().get_lockup(1).call().await?.unwrap();
+    assert_eq!(Some(LockupToken::Native), lockup.token);
+
+    let result = alice
+        .call(context.lockup().contract().id(), "claim_and_call")
+        .args_json(json!({ "receiver_id": carol.to_near(), "msg": "" }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(
+        result.into_result().is_err(),
+        "Native lockups can't be claimed with a call"
+    );
+
+    let balance = alice.view_account().await?.balance;
+    let claimed = context.lockup().claim(None).with_user(&alice).call().await?;
+    assert_eq!(amount.as_yoctonear(), claimed.0);
+    // the claimed NEAR is more than the gas alice has paid
+    assert!(alice.view_account().await?.balance > balance);
+
+    let balance = carol.view_account().await?.balance;
+    manager
+        .call(context.lockup().contract().id(), "terminate")
+        .args_json(json!({ "lockup_index": 1 }))
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(balance.saturating_add(amount), carol.view_account().await?.balance);
+
+    Ok(())
+}
+
+async fn create_native_lockup(
+    context: &Context,
+    creator: &Account,
+    lockup_create: Value,
+    deposit: NearToken,
+) -> Result<ExecutionFinalResult> {
+    Ok(creator
+        .call(context.lockup().contract().id(), "create_native_lockup")
+        .args_json(json!({ "lockup_create": lockup_create }))
+        .deposit(deposit)
+        .max_gas()
+        .transact()
+        .await?)
+}
@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage, lockup::LockupCreate, lockup_api::LockupApiIntegration, pause::PausableOperation,
+    role::Role, view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{json, to_string, Value};
+use near_workspaces::{result::ExecutionFinalResult, types::NearToken, Account};
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn paused_operations_are_stopped_until_unpaused() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    create_lockup(&context, &manager, &alice).await?;
+
+    let operations = json!({ "operations": [PausableOperation::Claim, PausableOperation::CreateLockup] });
+    let result = call_with_one_yocto(&context, &alice, "pause", operations.clone()).await?;
+    assert!(result.into_result().is_err(), "Only pausers can pause");
+
+    call_with_one_yocto(
+        &context,
+        &manager,
+        "grant_role",
+        json!({ "role": Role::Pauser, "account_ids": [bob.to_near()] }),
+    )
+    .await?
+    .into_result()?;
+    call_with_one_yocto(&context, &bob, "pause", operations.clone())
+        .await?
+        .into_result()?;
+    let paused_operations = context.lockup().get_paused_operations().call().await?;
+    assert_eq!(2, paused_operations.len());
+    assert!(!paused_operations.contains(&PausableOperation::ConvertDrafts));
+
+    let Err(error) = context.lockup().claim(None).with_user(&alice).call().await else {
+        panic!("Claims are paused");
+    };
+    assert!(error.to_string().contains("The operation Claim is paused"));
+
+    // the token is refunded to the creator
+    let balance = context.ft_contract().ft_balance_of(manager.to_near()).call().await?.0;
+    create_lockup(&context, &manager, &alice).await?;
+    assert_eq!(1, context.lockup().get_num_lockups().call().await?);
+    assert_eq!(
+        balance,
+        context.ft_contract().ft_balance_of(manager.to_near()).call().await?.0
+    );
+
+    call_with_one_yocto(&context, &bob, "unpause", operations)
+        .await?
+        .into_result()?;
+    assert!(context.lockup().get_paused_operations().call().await?.is_empty());
+
+    let claimed = context.lockup().claim(None).with_user(&alice).call().await?;
+    assert_eq!(100, claimed.0);
+    create_lockup(&context, &manager, &alice).await?;
+    assert_eq!(2, context.lockup().get_num_lockups().call().await?);
+
+    Ok(())
+}
+
+async fn create_lockup(context: &Context, manager: &Account, account: &Account) -> Result<()> {
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(account.to_near(), 100));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            100.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(manager)
+        .call()
+        .await?;
+
+    Ok(())
+}
+
+async fn call_with_one_yocto(
+    context: &Context,
+    account: &Account,
+    method: &str,
+    args: Value,
+) -> Result<ExecutionFinalResult> {
+    Ok(account
+        .call(context.lockup().contract().id(), method)
+        .args_json(args)
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?)
+}
@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::misc::ToNear;
+use model::{
+    ft_message::FtMessage, lockup::LockupCreate, lockup_api::LockupApiIntegration, storage::StorageApiIntegration,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::to_string;
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn pruned_lockup_keeps_final_state() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(alice.to_near(), 100));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            100.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    // a lockup with something left to claim can't be pruned
+    assert!(context.lockup().prune_lockups(vec![0]).call().await?.is_empty());
+
+    context.lockup().claim(None).with_user(&alice).call().await?;
+
+    let storage_balance = context
+        .lockup()
+        .storage_balance_of(manager.to_near())
+        .call()
+        .await?
+        .unwrap();
+
+    assert_eq!(vec![0], context.lockup().prune_lockups(vec![0]).call().await?);
+    assert!(context.lockup().prune_lockups(vec![0]).call().await?.is_empty());
+
+    let lockup = context
+        .lockup()
+        .get_lockup(0)
+        .call()
+        .await?
+        .expect("The pruned lockup is kept");
+    assert!(lockup.pruned);
+    assert_eq!(alice.to_near(), lockup.account_id);
+    assert_eq!(100, lockup.total_balance);
+    assert_eq!(100, lockup.claimed_balance);
+    assert_eq!(0, lockup.unclaimed_balance);
+    assert_eq!(1, context.lockup().get_num_lockups().call().await?);
+
+    let pruned_storage_balance = context
+        .lockup()
+        .storage_balance_of(manager.to_near())
+        .call()
+        .await?
+        .unwrap();
+    assert!(pruned_storage_balance.available.0 > storage_balance.available.0);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage, lockup::LockupCreate, lockup_api::LockupApiIntegration, role::Role,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{json, to_string};
+use near_workspaces::types::NearToken;
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn relayer_fee_is_limited_and_returned_on_failure() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    for (method, args) in [
+        ("add_to_relayers_whitelist", json!({ "account_ids": [bob.to_near()] })),
+        ("set_max_relayer_fee_bps", json!({ "max_fee_bps": 100 })),
+    ] {
+        manager
+            .call(context.lockup().contract().id(), method)
+            .args_json(args)
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    assert!(context.lockup().has_role(bob.to_near(), Role::Relayer).call().await?);
+    assert_eq!(
+        vec![bob.to_near()],
+        context.lockup().get_relayers_whitelist().call().await?
+    );
+
+    // bob isn't registered in the token, so the fee transfer fails
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(alice.to_near(), 1_000));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            1_000.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    let result = context
+        .lockup()
+        .claim_for(alice.to_near(), None, Some(11.into()))
+        .with_user(&bob)
+        .call()
+        .await;
+    assert!(result.is_err(), "The fee exceeds 1% of the claimed amount");
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(1_000, lockup.unclaimed_balance);
+
+    let claimed = context
+        .lockup()
+        .claim_for(alice.to_near(), None, Some(10.into()))
+        .with_user(&bob)
+        .call()
+        .await?;
+    assert_eq!(990, claimed.0);
+
+    // the fee returns to the lockup instead of going to the relayer
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(990, lockup.claimed_balance);
+    assert_eq!(10, lockup.unclaimed_balance);
+    assert_eq!(1, context.lockup().get_num_lockups().call().await?);
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{role::Role, view_api::LockupViewApiIntegration};
+use near_sdk::serde_json::json;
+use near_workspaces::types::NearToken;
+
+use crate::context::{prepare_contract, IntegrationContext};
+
+#[tokio::test]
+async fn deposit_whitelist_shims_manage_lockup_creators_only() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+
+    manager
+        .call(context.lockup().contract().id(), "add_to_deposit_whitelist")
+        .args_json(json!({ "account_id": alice.to_near() }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert!(
+        context
+            .lockup()
+            .has_role(alice.to_near(), Role::LockupCreator)
+            .call()
+            .await?
+    );
+    for role in [Role::Admin, Role::Terminator, Role::DraftOperator] {
+        assert!(!context.lockup().has_role(alice.to_near(), role).call().await?);
+    }
+    let whitelist = context.lockup().get_deposit_whitelist().call().await?;
+    assert!(whitelist.contains(&alice.to_near()));
+    assert!(whitelist.contains(&manager.to_near()));
+
+    manager
+        .call(context.lockup().contract().id(), "remove_from_deposit_whitelist")
+        .args_json(json!({ "account_ids": [alice.to_near(), manager.to_near()] }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert!(context.lockup().get_deposit_whitelist().call().await?.is_empty());
+    assert!(
+        !context
+            .lockup()
+            .has_role(alice.to_near(), Role::LockupCreator)
+            .call()
+            .await?
+    );
+    for role in [Role::Admin, Role::Terminator, Role::DraftOperator] {
+        assert!(context.lockup().has_role(manager.to_near(), role).call().await?);
+    }
+
+    Ok(())
+}
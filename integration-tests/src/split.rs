@@ -0,0 +1,173 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage,
+    lockup::{LockupCreate, LockupIndex, MAX_SPLIT_PORTIONS},
+    storage::StorageApiIntegration,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{from_value, json, to_string, Value};
+use near_workspaces::{types::NearToken, Account};
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn split_and_merged_lockups_are_paid_by_owner() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(alice.to_near(), 1_000));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            1_000.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    let portions: Vec<Value> = (0..=MAX_SPLIT_PORTIONS).map(|_| json!([bob.to_near(), "10"])).collect();
+    let result = call(
+        &context,
+        &alice,
+        "split_lockup",
+        json!({ "lockup_index": 0, "portions": portions }),
+        1,
+    )
+    .await;
+    assert!(result.is_err(), "The number of portions is limited");
+
+    let split_args = json!({ "lockup_index": 0, "portions": [[bob.to_near(), "100"]] });
+    let result = call(&context, &alice, "split_lockup", split_args.clone(), 1).await;
+    assert!(result.is_err(), "The storage of the new lockup should be paid");
+
+    alice
+        .call(context.lockup().contract().id(), "storage_deposit")
+        .deposit(NearToken::from_millinear(50))
+        .transact()
+        .await?
+        .into_result()?;
+    let initial_balance = storage_balance(&context, &alice).await?;
+
+    assert_eq!(json!([1]), call(&context, &alice, "split_lockup", split_args, 1).await?);
+    assert_eq!(100, total_balance(&context, 1).await?);
+    assert_eq!(900, total_balance(&context, 0).await?);
+    assert!(storage_balance(&context, &alice).await? < initial_balance);
+
+    let split_args = json!({ "lockup_index": 0, "portions": [[alice.to_near(), "200"]] });
+    assert_eq!(json!([2]), call(&context, &alice, "split_lockup", split_args, 1).await?);
+    let balance = storage_balance(&context, &alice).await?;
+
+    let merged_index: LockupIndex = from_value(
+        call(
+            &context,
+            &alice,
+            "merge_lockups",
+            json!({ "lockup_indices": [0, 2] }),
+            1,
+        )
+        .await?,
+    )?;
+    assert_eq!(3, merged_index);
+    assert_eq!(900, total_balance(&context, 3).await?);
+    // the storage of the merged split lockup is refunded, but the new one is charged
+    assert!(storage_balance(&context, &alice).await? < balance);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn remaining_lockup_keeps_storage_payment() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    alice
+        .call(context.lockup().contract().id(), "storage_deposit")
+        .deposit(NearToken::from_millinear(50))
+        .transact()
+        .await?
+        .into_result()?;
+    let initial_balance = storage_balance(&context, &manager).await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(alice.to_near(), 1_000));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            1_000.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+    let balance = storage_balance(&context, &manager).await?;
+
+    let split_args = json!({ "lockup_index": 0, "portions": [[bob.to_near(), "100"]] });
+    call(&context, &alice, "split_lockup", split_args, 1).await?;
+
+    // the remaining lockup is finished by the claim, so its storage is refunded to the creator
+    alice
+        .call(context.lockup().contract().id(), "claim")
+        .args_json(json!({ "amounts": [[0, "900"]] }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    let final_balance = storage_balance(&context, &manager).await?;
+    assert!(final_balance > balance);
+    assert!(final_balance < initial_balance);
+
+    Ok(())
+}
+
+async fn total_balance(context: &Context, index: LockupIndex) -> Result<u128> {
+    let lockup = context.lockup().get_lockup(index).call().await?.unwrap();
+    Ok(lockup.schedule.total_balance())
+}
+
+async fn storage_balance(context: &Context, account: &Account) -> Result<u128> {
+    let balance = context
+        .lockup()
+        .storage_balance_of(account.to_near())
+        .call()
+        .await?
+        .expect("The account has no storage balance");
+    Ok(balance.available.0)
+}
+
+async fn call(context: &Context, account: &Account, method: &str, args: Value, deposit: u128) -> Result<Value> {
+    Ok(account
+        .call(context.lockup().contract().id(), method)
+        .args_json(args)
+        .deposit(NearToken::from_yoctonear(deposit))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?
+        .json()?)
+}
@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::misc::ToNear;
+use model::{
+    ft_message::FtMessage, lockup::LockupCreate, lockup_api::LockupApiIntegration, storage::StorageApiIntegration,
+};
+use near_sdk::serde_json::to_string;
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn storage_is_paid_by_lockup_creator() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+
+    let initial_balance = context
+        .lockup()
+        .storage_balance_of(manager.to_near())
+        .call()
+        .await?
+        .expect("The manager has deposited for storage");
+    assert_eq!(initial_balance.total, initial_balance.available);
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    let message = FtMessage::LockupCreate(LockupCreate::new_unlocked(alice.to_near(), 100));
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            100.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    let balance = context
+        .lockup()
+        .storage_balance_of(manager.to_near())
+        .call()
+        .await?
+        .unwrap();
+    assert!(balance.available.0 < initial_balance.available.0);
+
+    // the claimed lockup is not indexed anymore, so a part of its storage is freed
+    context.lockup().claim(None).with_user(&alice).call().await?;
+
+    let final_balance = context
+        .lockup()
+        .storage_balance_of(manager.to_near())
+        .call()
+        .await?
+        .unwrap();
+    assert!(final_balance.available.0 > balance.available.0);
+    assert!(final_balance.available.0 < initial_balance.available.0);
+
+    // any account can have a storage balance to pay for its splits and merges
+    let balance = context
+        .lockup()
+        .storage_deposit(Some(alice.to_near()), Some(true))
+        .with_user(&alice)
+        .call()
+        .await?;
+    assert_eq!(0, balance.total.0);
+
+    Ok(())
+}
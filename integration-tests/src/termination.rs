@@ -0,0 +1,226 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage,
+    lockup::LockupCreate,
+    schedule::{Checkpoint, Schedule},
+    storage::StorageApiIntegration,
+    termination::VestingConditions,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::{
+    json_types::U128,
+    serde_json::{json, to_string, Value},
+    AccountId,
+};
+use near_workspaces::{result::ExecutionFinalResult, types::NearToken, Account};
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+#[tokio::test]
+async fn terminator_and_refund_receiver() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+    let carol = context.account("carol").await?;
+
+    context
+        .ft_contract()
+        .storage_deposit(carol.to_near().into(), None)
+        .call()
+        .await?;
+    create_lockup(&mut context, &alice, Some(bob.to_near()), Some(carol.to_near())).await?;
+    create_lockup(&mut context, &alice, None, None).await?;
+
+    // only the dedicated terminator can terminate the first lockup
+    let result = terminate(&context, &manager, 0).await?;
+    assert!(result.into_result().is_err(), "The lockup has its own terminator");
+    let result = terminate(&context, &bob, 1).await?;
+    assert!(result.into_result().is_err(), "Bob has no terminator role");
+
+    // bob has no storage balance, so the contract would pay for a refund lockup
+    let refund: U128 = terminate(&context, &bob, 0).await?.into_result()?.json()?;
+    assert_eq!(100, refund.0);
+    assert_eq!(
+        100,
+        context.ft_contract().ft_balance_of(carol.to_near()).call().await?.0
+    );
+
+    // the payer gets the refund of a lockup without a refund receiver
+    let balance = context.ft_contract().ft_balance_of(manager.to_near()).call().await?.0;
+    terminate(&context, &manager, 1).await?.into_result()?;
+    assert_eq!(
+        balance + 100,
+        context.ft_contract().ft_balance_of(manager.to_near()).call().await?.0
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_refund_is_locked_up_for_receiver() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let dave = context.account("dave").await?;
+
+    // dave isn't registered in the token, so the refund transfer fails
+    create_lockup(&mut context, &alice, None, Some(dave.to_near())).await?;
+    let initial_balance = storage_balance(&context, &manager).await?;
+
+    let refund: U128 = terminate(&context, &manager, 0).await?.into_result()?.json()?;
+    assert_eq!(0, refund.0);
+
+    let lockup = context.lockup().get_lockup(1).call().await?.unwrap();
+    assert_eq!(dave.to_near(), lockup.account_id);
+    assert_eq!(100, lockup.total_balance);
+    // the terminator pays for the refund lockup
+    assert!(storage_balance(&context, &manager).await? < initial_balance);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn termination_waits_for_grace_period() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+
+    create_lockup(&mut context, &alice, None, None).await?;
+    call_with_one_yocto(
+        &context,
+        &manager,
+        "set_termination_grace_period",
+        json!({ "grace_period": 100 }),
+    )
+    .await?
+    .into_result()?;
+
+    let result = terminate(&context, &manager, 0).await?;
+    assert!(
+        result.into_result().is_err(),
+        "The termination should be scheduled first"
+    );
+
+    // a cancelled termination can't be executed
+    let index = json!({ "lockup_index": 0 });
+    call_with_one_yocto(&context, &manager, "schedule_termination", index.clone())
+        .await?
+        .into_result()?;
+    call_with_one_yocto(&context, &manager, "cancel_termination", index.clone())
+        .await?
+        .into_result()?;
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    assert_eq!(None, lockup.pending_termination);
+
+    call_with_one_yocto(&context, &manager, "schedule_termination", index.clone())
+        .await?
+        .into_result()?;
+    let result = call_with_one_yocto(&context, &manager, "execute_termination", index.clone()).await?;
+    assert!(result.into_result().is_err(), "The grace period is not over yet");
+
+    let lockup = context.lockup().get_lockup(0).call().await?.unwrap();
+    let executable_at = lockup.pending_termination.unwrap().executable_at;
+    while context.worker.view_block().await?.timestamp() / 1_000_000_000 < executable_at {
+        context.worker.fast_forward(100).await?;
+    }
+
+    let balance = context.ft_contract().ft_balance_of(manager.to_near()).call().await?.0;
+    let refund: U128 = call_with_one_yocto(&context, &manager, "execute_termination", index)
+        .await?
+        .into_result()?
+        .json()?;
+    assert_eq!(100, refund.0);
+    assert_eq!(
+        balance + 100,
+        context.ft_contract().ft_balance_of(manager.to_near()).call().await?.0
+    );
+
+    Ok(())
+}
+
+/// Creates a lockup of 100 tokens for the account that vests in the far future.
+async fn create_lockup(
+    context: &mut Context,
+    account: &Account,
+    terminator_id: Option<AccountId>,
+    refund_receiver_id: Option<AccountId>,
+) -> Result<()> {
+    let manager = context.manager().await?;
+    let message = FtMessage::LockupCreate(LockupCreate {
+        account_id: account.to_near(),
+        schedule: Schedule::Checkpoints(vec![
+            Checkpoint {
+                timestamp: 4_000_000_000,
+                balance: 0,
+                interpolation: Default::default(),
+            },
+            Checkpoint {
+                timestamp: 4_100_000_000,
+                balance: 100,
+                interpolation: Default::default(),
+            },
+        ]),
+        vesting_schedule: Some(VestingConditions::SameAsLockupSchedule),
+        terminator_id,
+        refund_receiver_id,
+    });
+    context
+        .ft_contract()
+        .ft_transfer_call(
+            context.lockup().contract_account(),
+            100.into(),
+            None,
+            to_string(&message).unwrap(),
+        )
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    Ok(())
+}
+
+async fn terminate(context: &Context, terminator: &Account, lockup_index: u32) -> Result<ExecutionFinalResult> {
+    call_with_one_yocto(
+        context,
+        terminator,
+        "terminate",
+        json!({ "lockup_index": lockup_index }),
+    )
+    .await
+}
+
+async fn call_with_one_yocto(
+    context: &Context,
+    account: &Account,
+    method: &str,
+    args: Value,
+) -> Result<ExecutionFinalResult> {
+    Ok(account
+        .call(context.lockup().contract().id(), method)
+        .args_json(args)
+        .deposit(NearToken::from_yoctonear(1))
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+async fn storage_balance(context: &Context, account: &Account) -> Result<u128> {
+    let balance = context
+        .lockup()
+        .storage_balance_of(account.to_near())
+        .call()
+        .await?
+        .expect("The account has no storage balance");
+    Ok(balance.available.0)
+}
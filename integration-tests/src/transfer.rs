@@ -0,0 +1,110 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    ft_message::FtMessage,
+    lockup::{LockupCreate, LockupIndex},
+    schedule::Schedule,
+    termination::VestingConditions,
+    view_api::LockupViewApiIntegration,
+};
+use near_sdk::serde_json::{json, to_string, Value};
+use near_workspaces::{types::NearToken, Account};
+use sweat_model::{FungibleTokenCoreIntegration, StorageManagementIntegration};
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    lockup_interface::GetContractAccount,
+};
+
+async fn call_with_one_yocto(context: &Context, account: &Account, method: &str, args: Value) -> Result<()> {
+    account
+        .call(context.lockup().contract().id(), method)
+        .args_json(args)
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn terminable_lockup_transfer_waits_for_approval() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let manager = context.manager().await?;
+    let alice = context.alice().await?;
+    let bob = context.bob().await?;
+
+    context
+        .ft_contract()
+        .storage_deposit(alice.to_near().into(), None)
+        .call()
+        .await?;
+    // the first lockup is terminated by the terminator role members, the second one by bob only
+    for terminator_id in [None, Some(bob.to_near())] {
+        let message = FtMessage::LockupCreate(LockupCreate {
+            account_id: alice.to_near(),
+            schedule: Schedule::new_unlocked(100),
+            vesting_schedule: Some(VestingConditions::SameAsLockupSchedule),
+            terminator_id,
+            refund_receiver_id: None,
+        });
+        context
+            .ft_contract()
+            .ft_transfer_call(
+                context.lockup().contract_account(),
+                100.into(),
+                None,
+                to_string(&message).unwrap(),
+            )
+            .with_user(&manager)
+            .call()
+            .await?;
+    }
+
+    let transfer = |lockup_index: LockupIndex, receiver: &Account| json!({ "lockup_index": lockup_index, "receiver_id": receiver.to_near() });
+
+    call_with_one_yocto(&context, &alice, "transfer_lockup", transfer(0, &bob)).await?;
+    assert_eq!(
+        Some(bob.to_near()),
+        context.lockup().get_pending_lockup_transfer(0).call().await?
+    );
+    call_with_one_yocto(&context, &alice, "cancel_lockup_transfer", json!({ "lockup_index": 0 })).await?;
+    assert_eq!(None, context.lockup().get_pending_lockup_transfer(0).call().await?);
+
+    call_with_one_yocto(&context, &alice, "transfer_lockup", transfer(0, &bob)).await?;
+    let result = call_with_one_yocto(&context, &bob, "approve_lockup_transfer", json!({ "lockup_index": 0 })).await;
+    assert!(result.is_err(), "Only a terminator can approve the transfer");
+    call_with_one_yocto(
+        &context,
+        &manager,
+        "approve_lockup_transfer",
+        json!({ "lockup_index": 0 }),
+    )
+    .await?;
+    assert_eq!(
+        bob.to_near(),
+        context.lockup().get_lockup(0).call().await?.unwrap().account_id
+    );
+
+    // the lockup's own terminator approves the transfer instead of the role members
+    call_with_one_yocto(&context, &alice, "transfer_lockup", transfer(1, &manager)).await?;
+    let result = call_with_one_yocto(
+        &context,
+        &manager,
+        "approve_lockup_transfer",
+        json!({ "lockup_index": 1 }),
+    )
+    .await;
+    assert!(result.is_err(), "Only the lockup terminator can approve the transfer");
+    call_with_one_yocto(&context, &bob, "approve_lockup_transfer", json!({ "lockup_index": 1 })).await?;
+    assert_eq!(
+        manager.to_near(),
+        context.lockup().get_lockup(1).call().await?.unwrap().account_id
+    );
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::{
+    role::Role,
+    update::{ManagerApiIntegration, UpdateApiIntegration},
+};
+use near_sdk::serde_json::json;
+use near_workspaces::types::NearToken;
+
+use crate::context::{prepare_contract, IntegrationContext};
+
+#[tokio::test]
+async fn two_step_manager_transfer() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let bob = context.bob().await?;
+    let alice = context.alice().await?;
+    let manager = context.multisig().contract().as_account().clone();
+
+    assert_eq!(manager.to_near(), context.lockup().get_manager().call().await?);
+
+    let Err(error) = context
+        .lockup()
+        .propose_manager(Some(bob.to_near()))
+        .with_user(&alice)
+        .call()
+        .await
+    else {
+        panic!("Only the manager can propose a new one");
+    };
+    assert!(error.to_string().contains("Only the manager can propose a new manager"));
+
+    context
+        .lockup()
+        .propose_manager(Some(alice.to_near()))
+        .with_user(&manager)
+        .call()
+        .await?;
+    assert_eq!(
+        Some(alice.to_near()),
+        context.lockup().get_proposed_manager().call().await?
+    );
+    // the proposal doesn't change the manager until it's accepted
+    assert_eq!(manager.to_near(), context.lockup().get_manager().call().await?);
+
+    let Err(error) = context.lockup().accept_manager().with_user(&bob).call().await else {
+        panic!("Only the proposed account can accept the role");
+    };
+    assert!(error
+        .to_string()
+        .contains("Only the proposed manager can accept the role"));
+
+    context.lockup().accept_manager().with_user(&alice).call().await?;
+    assert_eq!(alice.to_near(), context.lockup().get_manager().call().await?);
+    assert_eq!(None, context.lockup().get_proposed_manager().call().await?);
+
+    let Err(error) = context
+        .lockup()
+        .propose_manager(Some(bob.to_near()))
+        .with_user(&manager)
+        .call()
+        .await
+    else {
+        panic!("The former manager can't propose a new one");
+    };
+    assert!(error.to_string().contains("Only the manager can propose a new manager"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn updater_cannot_set_multisig() -> Result<()> {
+    let mut context = prepare_contract().await?;
+
+    let admin = context.manager().await?;
+    let bob = context.bob().await?;
+    let manager = context.multisig().contract().as_account().clone();
+
+    admin
+        .call(context.lockup().contract().id(), "grant_role")
+        .args_json(json!({ "role": Role::Updater, "account_ids": [bob.to_near()] }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let Err(error) = context
+        .lockup()
+        .set_multisig(bob.to_near())
+        .with_user(&bob)
+        .call()
+        .await
+    else {
+        panic!("An updater can't take over the manager account");
+    };
+    assert!(error.to_string().contains("Only the manager can set the multisig"));
+    assert_eq!(manager.to_near(), context.lockup().get_manager().call().await?);
+
+    Ok(())
+}
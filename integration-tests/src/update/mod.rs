@@ -1,2 +1,4 @@
+pub mod manager;
 pub mod multisig;
 pub mod simple;
+pub mod timelock;
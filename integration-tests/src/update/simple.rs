@@ -1,8 +1,8 @@
 #![cfg(test)]
 
 use anyhow::Result;
-use integration_utils::misc::ToNear;
-use model::update::UpdateApiIntegration;
+use integration_utils::{integration_contract::IntegrationContract, misc::ToNear};
+use model::update::{ManagerApiIntegration, UpdateApiIntegration};
 
 use crate::{
     context::{prepare_contract, IntegrationContext},
@@ -15,8 +15,22 @@ async fn simple_update() -> Result<()> {
 
     let bob = context.bob().await?;
     let alice = context.alice().await?;
+    let manager = context.multisig().contract().as_account().clone();
 
-    context.lockup().set_multisig(alice.to_near()).call().await?;
+    context
+        .lockup()
+        .set_multisig(alice.to_near())
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    // the multisig is only proposed until it accepts the role
+    assert_eq!(manager.to_near(), context.lockup().get_manager().call().await?);
+    assert_eq!(
+        Some(alice.to_near()),
+        context.lockup().get_proposed_manager().call().await?
+    );
+    context.lockup().accept_manager().with_user(&alice).call().await?;
 
     let wasm = load_wasm("../res/hodl_lockup.wasm")?;
 
@@ -28,12 +42,12 @@ async fn simple_update() -> Result<()> {
         .await?;
 
     let Err(error) = context.lockup().update_contract(wasm).with_user(&bob).call().await else {
-        panic!("Update with non multisig account should fail");
+        panic!("Update with non manager account should fail");
     };
 
     assert!(error
         .to_string()
-        .contains("Only multisig account can update the contract"));
+        .contains("Only the manager or an updater can update the code"));
 
     Ok(())
 }
@@ -0,0 +1,169 @@
+#![cfg(test)]
+
+use anyhow::Result;
+use integration_utils::integration_contract::IntegrationContract;
+use model::{
+    update::{UpdateApiIntegration, UpdateTimelockApiIntegration},
+    TimestampSec,
+};
+use near_sdk::{json_types::Base58CryptoHash, serde_json::json};
+use near_workspaces::Account;
+
+use crate::{
+    context::{prepare_contract, Context, IntegrationContext},
+    utils::load_wasm,
+};
+
+const UPDATE_DELAY: TimestampSec = 60;
+
+#[tokio::test]
+async fn staged_update_is_applied_after_delay() -> Result<()> {
+    let mut context = prepare_contract().await?;
+    let manager = context.multisig().contract().as_account().clone();
+
+    let wasm = load_wasm("../res/hodl_lockup.wasm")?;
+    // the contract is already running the same code, so its hash is the hash of the wasm
+    let code_hash: Base58CryptoHash = context
+        .worker
+        .view_account(context.lockup().contract().id())
+        .await?
+        .code_hash
+        .0
+        .into();
+
+    context
+        .lockup()
+        .set_update_delay(UPDATE_DELAY)
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    let Err(error) = context
+        .lockup()
+        .update_contract(wasm.clone())
+        .with_user(&manager)
+        .call()
+        .await
+    else {
+        panic!("The code can't be updated without staging it");
+    };
+    assert!(error.to_string().contains("The update should be staged first"));
+
+    stage_update(&context, &manager, code_hash).await?;
+    let staged_update = context.lockup().get_staged_update().call().await?.unwrap();
+    assert_eq!(code_hash, staged_update.code_hash);
+
+    let Err(error) = context
+        .lockup()
+        .update_contract(wasm.clone())
+        .with_user(&manager)
+        .call()
+        .await
+    else {
+        panic!("The code can't be updated before the delay is over");
+    };
+    assert!(error.to_string().contains("The staged update is not active yet"));
+
+    wait_until(&context, staged_update.activation_timestamp).await?;
+
+    let other_wasm = load_wasm("../res/hodl_lockup_1.2.0.wasm")?;
+    let Err(error) = context
+        .lockup()
+        .update_contract(other_wasm)
+        .with_user(&manager)
+        .call()
+        .await
+    else {
+        panic!("Only the staged code can be deployed");
+    };
+    assert!(error.to_string().contains("The code doesn't match the staged update"));
+
+    context
+        .lockup()
+        .update_contract(wasm)
+        .with_user(&manager)
+        .call()
+        .await?;
+    assert_eq!(None, context.lockup().get_staged_update().call().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn restaging_update_restarts_delay() -> Result<()> {
+    let mut context = prepare_contract().await?;
+    let manager = context.multisig().contract().as_account().clone();
+
+    let wasm = load_wasm("../res/hodl_lockup.wasm")?;
+    let code_hash: Base58CryptoHash = context
+        .worker
+        .view_account(context.lockup().contract().id())
+        .await?
+        .code_hash
+        .0
+        .into();
+
+    context
+        .lockup()
+        .set_update_delay(UPDATE_DELAY)
+        .with_user(&manager)
+        .call()
+        .await?;
+
+    stage_update(&context, &manager, code_hash).await?;
+    let first_update = context.lockup().get_staged_update().call().await?.unwrap();
+    wait_until(&context, first_update.activation_timestamp).await?;
+
+    // staging the same code again is visible to the holders and postpones the update
+    stage_update(&context, &manager, code_hash).await?;
+    let second_update = context.lockup().get_staged_update().call().await?.unwrap();
+    assert!(second_update.activation_timestamp > first_update.activation_timestamp);
+
+    let Err(error) = context
+        .lockup()
+        .update_contract(wasm.clone())
+        .with_user(&manager)
+        .call()
+        .await
+    else {
+        panic!("The delay restarts once the update is staged again");
+    };
+    assert!(error.to_string().contains("The staged update is not active yet"));
+
+    let result = manager
+        .call(context.lockup().contract().id(), "cancel_update")
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(result.logs().iter().any(|log| log.contains("ft_lockup_cancel_update")));
+    assert_eq!(None, context.lockup().get_staged_update().call().await?);
+
+    wait_until(&context, second_update.activation_timestamp).await?;
+
+    let Err(error) = context.lockup().update_contract(wasm).with_user(&manager).call().await else {
+        panic!("A cancelled update can't be applied");
+    };
+    assert!(error.to_string().contains("The update should be staged first"));
+
+    Ok(())
+}
+
+async fn stage_update(context: &Context, manager: &Account, code_hash: Base58CryptoHash) -> Result<()> {
+    let result = manager
+        .call(context.lockup().contract().id(), "stage_update")
+        .args_json(json!({ "code_hash": code_hash }))
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(result.logs().iter().any(|log| log.contains("ft_lockup_stage_update")));
+
+    Ok(())
+}
+
+async fn wait_until(context: &Context, timestamp: TimestampSec) -> Result<()> {
+    while context.worker.view_block().await?.timestamp() / 1_000_000_000 <= u64::from(timestamp) {
+        context.worker.fast_forward(100).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use near_sdk::near;
+
+use crate::{preset::ONE_DAY_SEC, util::u128_dec_format, Balance, TimestampSec};
+
+const UNIX_EPOCH_YEAR: u16 = 1970;
+/// Days between 0000-03-01 and 1970-01-01 in the proleptic Gregorian calendar.
+const UNIX_EPOCH_DAYS: u64 = 719_468;
+const DAYS_IN_400_YEARS: u64 = 146_097;
+
+/// A date in UTC. It stands for the midnight at the beginning of the day.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CalendarDate {
+    pub year: u16,
+    /// 1-based month, January is 1.
+    pub month: u8,
+    /// 1-based day of the month.
+    pub day: u8,
+}
+
+impl CalendarDate {
+    pub fn assert_valid(&self) {
+        assert!(
+            self.year >= UNIX_EPOCH_YEAR,
+            "The date should not be earlier than {UNIX_EPOCH_YEAR}"
+        );
+        assert!((1..=12).contains(&self.month), "The month should be in range 1..=12");
+        assert!(
+            self.day >= 1 && self.day <= days_in_month(self.year, self.month),
+            "The day {} doesn't exist in {}-{:02}",
+            self.day,
+            self.year,
+            self.month
+        );
+        self.checked_timestamp()
+            .expect("The date is out of the supported range");
+    }
+
+    /// The unix-timestamp of the midnight of the date.
+    pub fn timestamp(&self) -> TimestampSec {
+        self.checked_timestamp()
+            .expect("The date is out of the supported range")
+    }
+
+    /// Returns the same day `months` months later.
+    /// The day is clamped to the length of the resulting month, e.g. Jan 31 + 1 month is Feb 28 (or 29).
+    pub fn add_months(&self, months: u32) -> Self {
+        let months = u32::from(self.year) * 12 + u32::from(self.month) - 1 + months;
+        let year = u16::try_from(months / 12).expect("The date is out of the supported range");
+        let month = (months % 12) as u8 + 1;
+        Self {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+        }
+    }
+
+    fn checked_timestamp(&self) -> Option<TimestampSec> {
+        // Shifting the year start to March 1st, so the leap day is the last day of the year.
+        let year = u64::from(self.year) - u64::from(self.month <= 2);
+        let month = u64::from(self.month);
+        let era = year / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + u64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = (era * DAYS_IN_400_YEARS + day_of_era).checked_sub(UNIX_EPOCH_DAYS)?;
+
+        (days * u64::from(ONE_DAY_SEC)).try_into().ok()
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalendarCheckpoint {
+    pub date: CalendarDate,
+    /// The balance unlocked since the beginning of the date.
+    #[serde(with = "u128_dec_format")]
+    pub balance: Balance,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: u16, month: u8, day: u8) -> CalendarDate {
+        CalendarDate { year, month, day }
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(date(1970, 1, 1).timestamp(), 0);
+        assert_eq!(date(2000, 3, 1).timestamp(), 951_868_800);
+        assert_eq!(date(2024, 2, 29).timestamp(), 1_709_164_800);
+        assert_eq!(date(2025, 1, 1).timestamp(), 1_735_689_600);
+        assert_eq!(date(2106, 2, 7).timestamp(), 4_294_944_000);
+        assert!(date(2106, 2, 8).checked_timestamp().is_none());
+    }
+
+    #[test]
+    fn add_months() {
+        assert_eq!(date(2024, 1, 31).add_months(1), date(2024, 2, 29));
+        assert_eq!(date(2025, 1, 31).add_months(1), date(2025, 2, 28));
+        assert_eq!(date(2024, 11, 1).add_months(3), date(2025, 2, 1));
+        assert_eq!(date(2024, 1, 1).add_months(48), date(2028, 1, 1));
+    }
+}
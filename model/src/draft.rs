@@ -8,8 +8,9 @@ use near_sdk::{
 
 use crate::{
     lockup::{LockupCreate, LockupCreateView},
+    storage::StoragePayment,
     util::u128_dec_format,
-    Balance,
+    Balance, TokenAccountId,
 };
 
 pub type DraftGroupIndex = u32;
@@ -20,6 +21,9 @@ pub type DraftIndex = u32;
 pub struct Draft {
     pub draft_group_id: DraftGroupIndex,
     pub lockup_create: LockupCreate,
+    /// Set by the contract when the draft is stored
+    #[serde(skip)]
+    pub storage_payment: Option<StoragePayment>,
 }
 
 impl Draft {
@@ -43,6 +47,8 @@ pub struct DraftGroup {
     pub payer_id: Option<AccountId>,
     pub draft_indices: HashSet<DraftIndex>,
     pub discarded: bool,
+    /// The token the group was funded with, `None` stands for the default token of the contract.
+    pub token_account_id: Option<TokenAccountId>,
 }
 
 impl DraftGroup {
@@ -61,9 +67,10 @@ impl DraftGroup {
         assert!(self.payer_id.is_none(), "draft group already funded");
     }
 
-    pub fn fund(&mut self, payer_id: &AccountId) {
+    pub fn fund(&mut self, payer_id: &AccountId, token_account_id: Option<TokenAccountId>) {
         self.assert_can_fund();
         self.payer_id = Some(payer_id.clone());
+        self.token_account_id = token_account_id;
     }
 
     pub fn assert_can_discard(&mut self) {
@@ -93,6 +100,7 @@ pub struct DraftGroupView {
     pub draft_indices: Vec<DraftIndex>,
     pub discarded: bool,
     pub funded: bool,
+    pub token_account_id: Option<TokenAccountId>,
 }
 
 impl From<DraftGroup> for DraftGroupView {
@@ -103,6 +111,7 @@ impl From<DraftGroup> for DraftGroupView {
             draft_indices: draft_group.draft_indices.into_iter().collect(),
             discarded: draft_group.discarded,
             funded: draft_group.payer_id.is_some(),
+            token_account_id: draft_group.token_account_id,
         }
     }
 }
@@ -1,10 +1,15 @@
 use near_sdk::{json_types::U128, AccountId};
 
+pub mod calendar;
 pub mod draft;
 pub mod ft_message;
 pub mod lockup;
 pub mod lockup_api;
+pub mod pause;
+pub mod preset;
+pub mod role;
 pub mod schedule;
+pub mod storage;
 pub mod termination;
 pub mod update;
 pub mod util;
@@ -5,14 +5,38 @@ use near_sdk::{
 };
 
 use crate::{
-    schedule::Schedule,
-    termination::{TerminationConfig, VestingConditions},
+    schedule::{split_proportionally, Schedule},
+    storage::StoragePayment,
+    termination::{PendingTermination, TerminationConfig, VestingConditions},
     util::{current_timestamp_sec, u128_dec_format},
-    Balance, TimestampSec, WrappedBalance,
+    Balance, TimestampSec, TokenAccountId, WrappedBalance,
 };
 
 pub type LockupIndex = u32;
 
+/// The maximum number of portions a lockup can be split into at once, so the split fits into the gas of a call.
+pub const MAX_SPLIT_PORTIONS: usize = 20;
+
+/// The asset that is locked.
+#[near(serializers=[borsh, json])]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum LockupToken {
+    /// A fungible token, claimed with `ft_transfer`
+    Ft(TokenAccountId),
+    /// Native NEAR, claimed with a transfer action
+    Native,
+}
+
+impl std::fmt::Display for LockupToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ft(token_account_id) => write!(f, "{token_account_id}"),
+            Self::Native => write!(f, "NEAR"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub struct LockupClaim {
@@ -21,6 +45,15 @@ pub struct LockupClaim {
     pub is_final: bool,
 }
 
+/// A claim whose tokens were transferred to the receiver.
+#[near(serializers=[borsh, json])]
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClaimRecord {
+    pub timestamp: TimestampSec,
+    pub amount: WrappedBalance,
+    pub receiver_id: AccountId,
+}
+
 #[near(serializers=[borsh, json])]
 #[derive(Debug, PartialEq, Clone)]
 pub struct Lockup {
@@ -32,6 +65,24 @@ pub struct Lockup {
     pub claimed_balance: Balance,
     /// An optional configuration that allows vesting/lockup termination.
     pub termination_config: Option<TerminationConfig>,
+    /// Unlocked tokens are pushed to the owner by `process_auto_claims`.
+    #[serde(default)]
+    pub auto_claim: bool,
+    /// The asset of the lockup, `None` stands for the default token of the contract.
+    #[serde(default)]
+    pub token: Option<LockupToken>,
+    /// A termination that waits for its grace period to be over.
+    #[serde(default)]
+    pub pending_termination: Option<PendingTermination>,
+    /// The unlocking and the vesting are frozen since this timestamp until the lockup is resumed.
+    #[serde(default)]
+    pub paused_at: Option<TimestampSec>,
+    /// The storage the creator of the lockup paid for, `None` if it's paid by the contract.
+    #[serde(default)]
+    pub storage_payment: Option<StoragePayment>,
+    /// Only the final state of a pruned lockup is kept, see `to_tombstone`.
+    #[serde(default)]
+    pub pruned: bool,
 }
 
 impl Lockup {
@@ -41,6 +92,12 @@ impl Lockup {
             schedule: Schedule::new_unlocked_since(total_balance, timestamp),
             claimed_balance: 0,
             termination_config: None,
+            auto_claim: false,
+            token: None,
+            pending_termination: None,
+            paused_at: None,
+            storage_payment: None,
+            pruned: false,
         }
     }
 
@@ -48,8 +105,41 @@ impl Lockup {
         Self::new_unlocked_since(account_id, total_balance, 1)
     }
 
+    /// Whether the whole balance of the lockup is claimed, a terminated lockup is finished once its vested part is.
+    pub fn is_finished(&self) -> bool {
+        self.claimed_balance == self.schedule.total_balance()
+    }
+
+    /// Returns the final state of a finished lockup, which takes less storage:
+    /// the schedule is reduced to the claimed balance and the configs of an active lockup are dropped.
+    pub fn to_tombstone(&self) -> Self {
+        assert!(self.is_finished(), "The lockup is not finished");
+        Self {
+            account_id: self.account_id.clone(),
+            schedule: Schedule::new_unlocked_since(self.claimed_balance, self.schedule.finish_timestamp().max(1)),
+            claimed_balance: self.claimed_balance,
+            termination_config: None,
+            auto_claim: false,
+            token: self.token.clone(),
+            pending_termination: None,
+            paused_at: None,
+            storage_payment: None,
+            pruned: true,
+        }
+    }
+
+    /// Returns the balance unlocked at `timestamp`, which stays as of the pause while the lockup is paused.
+    pub fn unlocked_balance(&self, timestamp: TimestampSec) -> Balance {
+        self.schedule.unlocked_balance(self.effective_timestamp(timestamp))
+    }
+
+    /// Returns `timestamp` limited by the pause, if any.
+    pub(crate) fn effective_timestamp(&self, timestamp: TimestampSec) -> TimestampSec {
+        self.paused_at.map_or(timestamp, |paused_at| paused_at.min(timestamp))
+    }
+
     pub fn claim(&mut self, index: LockupIndex, claim_amount: Balance) -> LockupClaim {
-        let unlocked_balance = self.schedule.unlocked_balance(current_timestamp_sec());
+        let unlocked_balance = self.unlocked_balance(current_timestamp_sec());
         let balance_claimed_new = self
             .claimed_balance
             .checked_add(claim_amount)
@@ -67,6 +157,104 @@ impl Lockup {
         }
     }
 
+    /// Splits off new lockups with the given total balances, this lockup keeps the remaining balance.
+    /// The schedule, the claimed balance and the vesting schedule are divided proportionally.
+    pub fn split(&mut self, portions: &[(AccountId, Balance)]) -> Vec<Lockup> {
+        assert!(!portions.is_empty(), "expected at least one portion");
+        assert!(
+            portions.len() <= MAX_SPLIT_PORTIONS,
+            "The lockup can be split into at most {MAX_SPLIT_PORTIONS} portions at once"
+        );
+        assert!(
+            self.pending_termination.is_none(),
+            "A lockup with a pending termination can't be split"
+        );
+        assert!(self.paused_at.is_none(), "A paused lockup can't be split");
+        assert!(
+            portions.iter().all(|(_, balance)| *balance > 0),
+            "expected portion balances to be positive"
+        );
+        let timestamp = current_timestamp_sec();
+        let total_balance = self.schedule.total_balance();
+        let split_balance = portions
+            .iter()
+            .try_fold(0, |sum: Balance, (_, balance)| sum.checked_add(*balance))
+            .expect("attempt to add with overflow");
+        assert!(
+            split_balance < total_balance,
+            "The portions should be less than the lockup balance"
+        );
+        let balances: Vec<Balance> = std::iter::once(total_balance - split_balance)
+            .chain(portions.iter().map(|(_, balance)| *balance))
+            .collect();
+
+        let schedules = self.schedule.split(&balances, timestamp);
+        let vesting_schedules = match self.termination_config.as_ref().map(|config| &config.vesting_schedule) {
+            Some(VestingConditions::Schedule(schedule)) => Some(schedule.split(&balances, timestamp)),
+            Some(VestingConditions::Hash(_)) => panic!("A lockup with a hashed vesting schedule can't be split"),
+            Some(VestingConditions::SameAsLockupSchedule) | None => None,
+        };
+
+        // The claimed balance can't exceed the unlocked one, so the rounding excess goes to the lockups
+        // that have room for it.
+        let unlocked_balances: Vec<Balance> = schedules
+            .iter()
+            .map(|schedule| schedule.unlocked_balance(timestamp))
+            .collect();
+        let mut claimed_balances: Vec<Balance> = split_proportionally(self.claimed_balance, &balances, total_balance)
+            .into_iter()
+            .zip(&unlocked_balances)
+            .map(|(claimed_balance, &unlocked_balance)| claimed_balance.min(unlocked_balance))
+            .collect();
+        let mut excess = self.claimed_balance - claimed_balances.iter().sum::<Balance>();
+        for (claimed_balance, &unlocked_balance) in claimed_balances.iter_mut().zip(&unlocked_balances) {
+            let moved = excess.min(unlocked_balance - *claimed_balance);
+            *claimed_balance += moved;
+            excess -= moved;
+        }
+        assert_eq!(excess, 0, "Invariant");
+
+        let account_ids = std::iter::once(&self.account_id).chain(portions.iter().map(|(account_id, _)| account_id));
+        let mut lockups: Vec<Lockup> = account_ids
+            .zip(schedules)
+            .zip(claimed_balances)
+            .enumerate()
+            .map(|(index, ((account_id, schedule), claimed_balance))| {
+                let termination_config = self.termination_config.as_ref().map(|config| TerminationConfig {
+                    beneficiary_id: config.beneficiary_id.clone(),
+                    terminator_id: config.terminator_id.clone(),
+                    vesting_schedule: match &vesting_schedules {
+                        Some(vesting_schedules) => {
+                            let vesting_schedule = &vesting_schedules[index];
+                            schedule.assert_valid_termination_schedule(vesting_schedule);
+                            VestingConditions::Schedule(vesting_schedule.clone())
+                        }
+                        None => config.vesting_schedule.clone(),
+                    },
+                });
+                Lockup {
+                    account_id: account_id.clone(),
+                    schedule,
+                    claimed_balance,
+                    termination_config,
+                    // the opt-in is up to the new owner
+                    auto_claim: self.auto_claim && *account_id == self.account_id,
+                    token: self.token.clone(),
+                    pending_termination: None,
+                    paused_at: None,
+                    storage_payment: None,
+                    pruned: false,
+                }
+            })
+            .collect();
+
+        // the remaining lockup takes the same storage, so it's still covered by the original payment
+        let storage_payment = self.storage_payment.take();
+        *self = lockups.remove(0);
+        self.storage_payment = storage_payment;
+        lockups
+    }
+
     pub fn assert_new_valid(&self, total_balance: Balance) {
         assert_eq!(
             self.claimed_balance, 0,
@@ -97,6 +285,10 @@ pub struct LockupCreate {
     pub account_id: AccountId,
     pub schedule: Schedule,
     pub vesting_schedule: Option<VestingConditions>,
+    /// The account that can terminate the lockup instead of the terminator role members, requires `vesting_schedule`
+    pub terminator_id: Option<AccountId>,
+    /// The account that receives the unvested balance instead of the payer, requires `vesting_schedule`
+    pub refund_receiver_id: Option<AccountId>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -106,21 +298,34 @@ impl LockupCreate {
             account_id,
             schedule: Schedule::new_unlocked(total_balance),
             vesting_schedule: None,
+            terminator_id: None,
+            refund_receiver_id: None,
         }
     }
 }
 
 impl LockupCreate {
     pub fn into_lockup(&self, payer_id: &AccountId) -> Lockup {
+        assert!(
+            self.vesting_schedule.is_some() || (self.terminator_id.is_none() && self.refund_receiver_id.is_none()),
+            "A terminator or a refund receiver can be specified only for a lockup with a vesting schedule"
+        );
         let vesting_schedule = self.vesting_schedule.clone();
         Lockup {
             account_id: self.account_id.clone(),
             schedule: self.schedule.clone(),
             claimed_balance: 0,
             termination_config: vesting_schedule.map(|vesting_schedule| TerminationConfig {
-                beneficiary_id: payer_id.clone(),
+                beneficiary_id: self.refund_receiver_id.clone().unwrap_or_else(|| payer_id.clone()),
                 vesting_schedule,
+                terminator_id: self.terminator_id.clone(),
             }),
+            auto_claim: false,
+            token: None,
+            pending_termination: None,
+            paused_at: None,
+            storage_payment: None,
+            pruned: false,
         }
     }
 }
@@ -136,6 +341,18 @@ pub struct LockupView {
     pub claimed_balance: Balance,
     /// An optional configuration that allows vesting/lockup termination.
     pub termination_config: Option<TerminationConfig>,
+    #[serde(default)]
+    pub auto_claim: bool,
+    /// The asset of the lockup, `None` stands for the default token of the contract.
+    #[serde(default)]
+    pub token: Option<LockupToken>,
+    #[serde(default)]
+    pub pending_termination: Option<PendingTermination>,
+    #[serde(default)]
+    pub paused_at: Option<TimestampSec>,
+    /// The lockup is finished and only its final state is kept
+    #[serde(default)]
+    pub pruned: bool,
 
     #[serde(with = "u128_dec_format")]
     pub total_balance: Balance,
@@ -149,18 +366,30 @@ impl From<Lockup> for LockupView {
     fn from(lockup: Lockup) -> Self {
         let total_balance = lockup.schedule.total_balance();
         let timestamp = current_timestamp_sec();
-        let unclaimed_balance = lockup.schedule.unlocked_balance(timestamp) - lockup.claimed_balance;
+        let unclaimed_balance = lockup.unlocked_balance(timestamp) - lockup.claimed_balance;
         let Lockup {
             account_id,
             schedule,
             claimed_balance,
             termination_config,
+            auto_claim,
+            token,
+            pending_termination,
+            paused_at,
+            storage_payment: _,
+            pruned,
         } = lockup;
         Self {
             account_id,
-            schedule,
+            // Parametric schedules are shown expanded, so views look the same for any schedule
+            schedule: Schedule::Checkpoints(schedule.checkpoints().into_owned()),
             claimed_balance,
             termination_config,
+            auto_claim,
+            token,
+            pending_termination,
+            paused_at,
+            pruned,
             total_balance,
             unclaimed_balance,
             timestamp,
@@ -174,6 +403,8 @@ pub struct LockupCreateView {
     pub account_id: AccountId,
     pub schedule: Schedule,
     pub vesting_schedule: Option<VestingConditions>,
+    pub terminator_id: Option<AccountId>,
+    pub refund_receiver_id: Option<AccountId>,
 
     #[serde(with = "u128_dec_format")]
     pub claimed_balance: Balance,
@@ -194,11 +425,15 @@ impl From<LockupCreate> for LockupCreateView {
             account_id,
             schedule,
             vesting_schedule,
+            terminator_id,
+            refund_receiver_id,
         } = lockup_create;
         Self {
             account_id,
             schedule,
             vesting_schedule,
+            terminator_id,
+            refund_receiver_id,
             claimed_balance: 0,
             total_balance,
             unclaimed_balance,
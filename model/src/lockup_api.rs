@@ -3,9 +3,12 @@ use nitka::make_integration_version;
 
 use crate::{
     draft::{Draft, DraftGroupIndex, DraftIndex},
-    lockup::LockupIndex,
+    lockup::{LockupCreate, LockupIndex, LockupToken},
+    pause::PausableOperation,
+    role::Role,
     schedule::Schedule,
-    TimestampSec, WrappedBalance,
+    termination::LockupReduction,
+    TimestampSec, TokenAccountId, WrappedBalance,
 };
 
 #[make_integration_version]
@@ -17,8 +20,55 @@ pub trait LockupApi {
         manager: AccountId,
     ) -> Self;
 
+    /// Creates a lockup of native NEAR, its balance is the attached deposit.
+    /// Can be called only by a lockup creator, who becomes the termination beneficiary.
+    fn create_native_lockup(&mut self, lockup_create: LockupCreate) -> LockupIndex;
+
+    /// Claims unlocked balances of the predecessor's lockups, with a transfer per token.
+    /// Returns the claimed amount once the transfers are complete, summed over all tokens if there are several,
+    /// the amounts of failed transfers are returned to the lockups and not counted.
     fn claim(&mut self, amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>) -> PromiseOrValue<WrappedBalance>;
 
+    /// Same as `claim`, but transfers the claimed tokens to `receiver_id`.
+    fn claim_to(
+        &mut self,
+        receiver_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> PromiseOrValue<WrappedBalance>;
+
+    /// Claims as `claim` does and transfers the tokens to `receiver_id` with `ft_transfer_call`.
+    /// The amount returned by the receiver goes back to the lockups. Native lockups can't be claimed this way.
+    fn claim_and_call(
+        &mut self,
+        receiver_id: AccountId,
+        msg: String,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+    ) -> PromiseOrValue<WrappedBalance>;
+
+    /// Claims on behalf of `account_id`, can be called only by a relayer.
+    /// The tokens go to the lockup owner, except for the optional `fee` that is paid to the relayer.
+    /// The fee can't exceed the share of the claimed amount set by `set_max_relayer_fee_bps`,
+    /// it returns to the lockups if the transfer to the relayer fails.
+    fn claim_for(
+        &mut self,
+        account_id: AccountId,
+        amounts: Option<Vec<(LockupIndex, Option<WrappedBalance>)>>,
+        fee: Option<WrappedBalance>,
+    ) -> PromiseOrValue<WrappedBalance>;
+
+    /// Opts the predecessor's lockups in or out of auto-claims.
+    fn set_auto_claim(&mut self, lockup_indices: Vec<LockupIndex>, auto_claim: bool);
+
+    /// Transfers unlocked balances of the opted-in lockups in the range `from_index..from_index + limit`
+    /// to their owners. Returns the index to continue from once the transfers are complete,
+    /// which is less than the end of the range if the gas has run out.
+    fn process_auto_claims(&mut self, from_index: LockupIndex, limit: LockupIndex) -> PromiseOrValue<LockupIndex>;
+
+    /// Terminates the lockup, can be called by its terminator or, if it has none, by an account with the terminator role.
+    /// The unvested balance is transferred to the termination beneficiary. Requires one yoctoNEAR.
+    /// If the transfer fails, the balance is locked up for the beneficiary, its storage is paid from
+    /// 500 bytes reserved from the predecessor's storage balance or by the contract if there are not enough.
+    /// Not available if there is a termination grace period, see `schedule_termination`.
     fn terminate(
         &mut self,
         lockup_index: LockupIndex,
@@ -26,16 +76,117 @@ pub trait LockupApi {
         termination_timestamp: Option<TimestampSec>,
     ) -> PromiseOrValue<WrappedBalance>;
 
+    /// Announces a termination that can be executed once the termination grace period is over.
+    /// The vesting stops at `termination_timestamp`, which is the current timestamp if not specified.
+    fn schedule_termination(&mut self, lockup_index: LockupIndex, termination_timestamp: Option<TimestampSec>);
+
+    fn cancel_termination(&mut self, lockup_index: LockupIndex);
+
+    /// Terminates the lockup as announced by `schedule_termination`, the same as `terminate` does.
+    fn execute_termination(
+        &mut self,
+        lockup_index: LockupIndex,
+        hashed_schedule: Option<Schedule>,
+    ) -> PromiseOrValue<WrappedBalance>;
+
+    /// Takes back a part of the unvested balance, the lockup keeps vesting the rest on its schedule scaled down.
+    /// Can be called by whoever can terminate the lockup, the taken balance goes to the termination beneficiary.
+    /// Requires one yoctoNEAR, a failed transfer is handled the same way as in `terminate`.
+    fn reduce_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        reduction: LockupReduction,
+        hashed_schedule: Option<Schedule>,
+    ) -> PromiseOrValue<WrappedBalance>;
+
+    /// Freezes the unlocking and the vesting of the lockup, e.g. for an unpaid leave.
+    /// Can be called by whoever can terminate the lockup.
+    fn pause_lockup(&mut self, lockup_index: LockupIndex);
+
+    /// Unfreezes the paused lockup, the rest of its lockup and vesting schedules is delayed by the pause length.
+    /// A hashed vesting schedule has to be revealed.
+    fn resume_lockup(&mut self, lockup_index: LockupIndex, hashed_schedule: Option<Schedule>);
+
+    fn set_termination_grace_period(&mut self, grace_period: TimestampSec);
+
+    /// Sets how many of the latest claims are kept for each lockup, the history isn't kept if it's zero.
+    /// Can be called by an admin or the manager.
+    fn set_claim_history_limit(&mut self, limit: u32);
+
+    /// Moves the lockup of the predecessor to another account.
+    /// A terminable lockup is only moved once the transfer is approved by whoever can terminate the lockup.
+    fn transfer_lockup(&mut self, lockup_index: LockupIndex, receiver_id: AccountId);
+
+    /// Moves the lockup to the requested receiver, can be called by whoever can terminate the lockup.
+    fn approve_lockup_transfer(&mut self, lockup_index: LockupIndex);
+
+    /// Drops the pending transfer of the lockup, can be called by its owner or whoever can terminate it.
+    fn cancel_lockup_transfer(&mut self, lockup_index: LockupIndex);
+
+    /// Splits off parts of the predecessor's lockup into new lockups for the given accounts,
+    /// at most `MAX_SPLIT_PORTIONS` at once. The storage of the new lockups is paid from the predecessor's
+    /// storage balance. Requires one yoctoNEAR. Returns indices of the new lockups.
+    /// A terminable lockup can only be split into lockups of its owner, since moving it to another account
+    /// requires an approval of the terminator: split it first and move the new lockup with `transfer_lockup`.
+    fn split_lockup(
+        &mut self,
+        lockup_index: LockupIndex,
+        portions: Vec<(AccountId, WrappedBalance)>,
+    ) -> Vec<LockupIndex>;
+
+    /// Merges non-terminable lockups of the predecessor into a single lockup, the lockups should be of one token.
+    /// If `lockup_indices` are not specified, all lockups of `token` are merged,
+    /// the default token is used if it's not specified either. Returns the index of the new lockup.
+    /// The merged lockups are pruned, keeping only their claimed balance.
+    /// The storage of the new lockup is paid from the predecessor's storage balance. Requires one yoctoNEAR.
+    fn merge_lockups(&mut self, lockup_indices: Option<Vec<LockupIndex>>, token: Option<LockupToken>) -> LockupIndex;
+
+    /// Replaces finished lockups with their final state to free the storage, the lockups keep their indices.
+    /// The claim history of the pruned lockups is kept, its storage stays paid by their storage payers.
+    /// Lockups that are not finished or have a claim in progress are skipped. Returns indices of the pruned lockups.
+    fn prune_lockups(&mut self, lockup_indices: Vec<LockupIndex>) -> Vec<LockupIndex>;
+
+    /// Grants the role to the accounts, can be called by an admin or the manager.
+    fn grant_role(&mut self, role: Role, account_ids: Vec<AccountId>);
+
+    /// Revokes the role from the accounts, can be called by an admin or the manager.
+    /// At least one admin has to remain.
+    fn revoke_role(&mut self, role: Role, account_ids: Vec<AccountId>);
+
+    /// Grants the lockup creator role, the other roles of the former deposit whitelist
+    /// (see `Role::DEPOSIT_WHITELIST`) are granted with `grant_role`.
     // preserving both options for API compatibility
     fn add_to_deposit_whitelist(&mut self, account_id: Option<AccountId>, account_ids: Option<Vec<AccountId>>);
 
+    /// Revokes the lockup creator role, the other roles are kept.
     // preserving both options for API compatibility
     fn remove_from_deposit_whitelist(&mut self, account_id: Option<AccountId>, account_ids: Option<Vec<AccountId>>);
 
+    /// Grants the draft operator role.
     fn add_to_draft_operators_whitelist(&mut self, account_ids: Vec<AccountId>);
 
     fn remove_from_draft_operators_whitelist(&mut self, account_ids: Vec<AccountId>);
 
+    /// Grants the relayer role.
+    fn add_to_relayers_whitelist(&mut self, account_ids: Vec<AccountId>);
+
+    fn remove_from_relayers_whitelist(&mut self, account_ids: Vec<AccountId>);
+
+    /// Sets the maximum relayer fee in basis points of the claimed amount, relayers take no fee if it's zero.
+    /// Can be called by an admin or the manager.
+    fn set_max_relayer_fee_bps(&mut self, max_fee_bps: u16);
+
+    /// Tokens that are accepted for lockups in addition to the default one.
+    fn add_to_token_whitelist(&mut self, token_account_ids: Vec<TokenAccountId>);
+
+    fn remove_from_token_whitelist(&mut self, token_account_ids: Vec<TokenAccountId>);
+
+    /// Stops the given operations until they are unpaused, can be called by the manager or a pauser.
+    /// Refunds of failed transfers keep working while the operations are paused.
+    fn pause(&mut self, operations: Vec<PausableOperation>);
+
+    fn unpause(&mut self, operations: Vec<PausableOperation>);
+
     fn create_draft_group(&mut self) -> DraftGroupIndex;
 
     fn create_draft(&mut self, draft: Draft) -> DraftIndex;
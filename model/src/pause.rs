@@ -0,0 +1,14 @@
+use near_sdk::near;
+
+/// A class of operations that the manager can stop in case of an incident.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableOperation {
+    /// Claims of unlocked balances, including relayed and automatic ones
+    Claim,
+    /// Lockup creations and draft group fundings, both with fungible tokens and native NEAR
+    CreateLockup,
+    /// Conversions of drafts into lockups
+    ConvertDrafts,
+}
@@ -0,0 +1,278 @@
+use near_sdk::near;
+
+use crate::{
+    calendar::{CalendarCheckpoint, CalendarDate},
+    schedule::{proportion, Checkpoint, Interpolation, ParametricSchedule, Schedule},
+    Balance, TimestampSec,
+};
+
+pub const ONE_DAY_SEC: TimestampSec = 24 * 60 * 60;
+pub const ONE_YEAR_SEC: TimestampSec = 365 * ONE_DAY_SEC;
+/// An average month, i.e. a twelfth of a non-leap year.
+pub const ONE_MONTH_SEC: TimestampSec = ONE_YEAR_SEC / 12;
+
+/// Length of a single unlock step of a periodic schedule.
+#[near(serializers=[json])]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockPeriod {
+    Day,
+    Month,
+    Quarter,
+}
+
+impl UnlockPeriod {
+    pub fn duration(self) -> TimestampSec {
+        match self {
+            Self::Day => ONE_DAY_SEC,
+            Self::Month => ONE_MONTH_SEC,
+            Self::Quarter => 3 * ONE_MONTH_SEC,
+        }
+    }
+}
+
+/// A named vesting shape that expands into a canonical `Schedule` for a given total balance.
+#[near(serializers=[json])]
+#[derive(Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchedulePreset {
+    /// Nothing is unlocked until the cliff, then the balance accrued so far is released at once
+    /// and the rest unlocks linearly until `start + duration`.
+    CliffLinear {
+        start: TimestampSec,
+        cliff_duration: TimestampSec,
+        duration: TimestampSec,
+    },
+    /// Equal portions are released at the end of each of `count` periods.
+    Periodic {
+        start: TimestampSec,
+        period: UnlockPeriod,
+        count: u32,
+    },
+    /// Equal portions are released at the beginning of the same day of each of `count` calendar months
+    /// following `start`.
+    CalendarMonthly { start: CalendarDate, count: u32 },
+    /// `immediate_percent` of the balance is released at `start`,
+    /// the remainder unlocks linearly until `start + duration`.
+    ImmediateLinear {
+        start: TimestampSec,
+        immediate_percent: u8,
+        duration: TimestampSec,
+    },
+}
+
+impl SchedulePreset {
+    pub fn build(&self, total_balance: Balance) -> Schedule {
+        match *self {
+            Self::CliffLinear {
+                start,
+                cliff_duration,
+                duration,
+            } => Schedule::new_cliff_linear(total_balance, start, cliff_duration, duration),
+            Self::Periodic { start, period, count } => Schedule::new_periodic(total_balance, start, period, count),
+            Self::CalendarMonthly { start, count } => Schedule::new_calendar_monthly(total_balance, start, count),
+            Self::ImmediateLinear {
+                start,
+                immediate_percent,
+                duration,
+            } => Schedule::new_immediate_and_linear(total_balance, start, immediate_percent, duration),
+        }
+    }
+}
+
+impl Schedule {
+    pub fn new_cliff_linear(
+        total_balance: Balance,
+        start: TimestampSec,
+        cliff_duration: TimestampSec,
+        duration: TimestampSec,
+    ) -> Self {
+        assert!(total_balance > 0, "expected total balance to be positive");
+        assert!(duration > 0, "expected duration to be positive");
+        assert!(
+            cliff_duration <= duration,
+            "The cliff duration should not exceed the total duration"
+        );
+        let finish = start.checked_add(duration).expect("attempt to add with overflow");
+
+        let mut checkpoints = vec![Checkpoint {
+            timestamp: start,
+            balance: 0,
+            interpolation: Interpolation::Linear,
+        }];
+        if cliff_duration > 0 {
+            checkpoints.push(Checkpoint {
+                timestamp: start + cliff_duration,
+                balance: proportion(total_balance, cliff_duration.into(), duration.into()),
+                interpolation: Interpolation::StepAtEnd,
+            });
+        }
+        if checkpoints.last().unwrap().timestamp < finish {
+            checkpoints.push(Checkpoint {
+                timestamp: finish,
+                balance: total_balance,
+                interpolation: Interpolation::Linear,
+            });
+        }
+
+        Self::Checkpoints(checkpoints)
+    }
+
+    pub fn new_periodic(total_balance: Balance, start: TimestampSec, period: UnlockPeriod, count: u32) -> Self {
+        assert!(total_balance > 0, "expected total balance to be positive");
+        let schedule = ParametricSchedule {
+            start,
+            cliff: 0,
+            period: period.duration(),
+            count,
+            total_balance,
+        };
+        schedule.assert_valid(total_balance);
+
+        Self::Checkpoints(schedule.checkpoints())
+    }
+
+    pub fn new_calendar_monthly(total_balance: Balance, start: CalendarDate, count: u32) -> Self {
+        assert!(total_balance > 0, "expected total balance to be positive");
+        assert!(count > 0, "expected count to be positive");
+
+        let mut checkpoints = vec![CalendarCheckpoint {
+            date: start,
+            balance: 0,
+        }];
+        for i in 1..=count {
+            checkpoints.push(CalendarCheckpoint {
+                date: start.add_months(i),
+                balance: proportion(total_balance, i.into(), count.into()),
+            });
+        }
+        let schedule = Self::Calendar(checkpoints);
+        schedule.assert_valid(total_balance);
+
+        schedule
+    }
+
+    pub fn new_immediate_and_linear(
+        total_balance: Balance,
+        start: TimestampSec,
+        immediate_percent: u8,
+        duration: TimestampSec,
+    ) -> Self {
+        assert!(total_balance > 0, "expected total balance to be positive");
+        assert!(duration > 0, "expected duration to be positive");
+        assert!(start > 0, "expected start to be positive");
+        assert!(immediate_percent <= 100, "The immediate percent should not exceed 100");
+        let finish = start.checked_add(duration).expect("attempt to add with overflow");
+
+        let immediate_balance = proportion(total_balance, immediate_percent.into(), 100);
+        if immediate_balance == 0 {
+            return Self::Checkpoints(vec![
+                Checkpoint {
+                    timestamp: start,
+                    balance: 0,
+                    interpolation: Interpolation::Linear,
+                },
+                Checkpoint {
+                    timestamp: finish,
+                    balance: total_balance,
+                    interpolation: Interpolation::Linear,
+                },
+            ]);
+        }
+        if immediate_balance == total_balance {
+            return Self::new_unlocked_since(total_balance, start);
+        }
+
+        Self::Checkpoints(vec![
+            Checkpoint {
+                timestamp: start - 1,
+                balance: 0,
+                interpolation: Interpolation::Linear,
+            },
+            Checkpoint {
+                timestamp: start,
+                balance: immediate_balance,
+                interpolation: Interpolation::Linear,
+            },
+            Checkpoint {
+                timestamp: finish,
+                balance: total_balance,
+                interpolation: Interpolation::Linear,
+            },
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: TimestampSec = 1_600_000_000;
+    const TOTAL: Balance = 48_000;
+
+    #[test]
+    fn cliff_linear() {
+        let schedule = Schedule::new_cliff_linear(TOTAL, START, ONE_YEAR_SEC, ONE_YEAR_SEC * 4);
+        schedule.assert_valid(TOTAL);
+
+        assert_eq!(schedule.unlocked_balance(START + ONE_YEAR_SEC - 1), 0);
+        assert_eq!(schedule.unlocked_balance(START + ONE_YEAR_SEC), TOTAL / 4);
+        assert_eq!(schedule.unlocked_balance(START + ONE_YEAR_SEC * 2), TOTAL / 2);
+        assert_eq!(schedule.unlocked_balance(START + ONE_YEAR_SEC * 4), TOTAL);
+    }
+
+    #[test]
+    fn cliff_linear_edge_cliffs() {
+        for cliff_duration in [0, 1, 2, ONE_DAY_SEC] {
+            Schedule::new_cliff_linear(TOTAL, START, cliff_duration, ONE_DAY_SEC).assert_valid(TOTAL);
+        }
+    }
+
+    #[test]
+    fn periodic() {
+        for period in [UnlockPeriod::Day, UnlockPeriod::Month, UnlockPeriod::Quarter] {
+            let schedule = Schedule::new_periodic(TOTAL, START, period, 48);
+            schedule.assert_valid(TOTAL);
+
+            let duration = period.duration();
+            assert_eq!(schedule.unlocked_balance(START + duration - 1), 0);
+            assert_eq!(schedule.unlocked_balance(START + duration), TOTAL / 48);
+            assert_eq!(schedule.unlocked_balance(START + duration * 2 - 1), TOTAL / 48);
+            assert_eq!(schedule.unlocked_balance(START + duration * 48), TOTAL);
+        }
+    }
+
+    #[test]
+    fn calendar_monthly() {
+        let start = CalendarDate {
+            year: 2024,
+            month: 1,
+            day: 1,
+        };
+        let schedule = Schedule::new_calendar_monthly(TOTAL, start, 48);
+        schedule.assert_valid(TOTAL);
+
+        let february = start.add_months(1).timestamp();
+        let march = start.add_months(2).timestamp();
+        assert_eq!(march - february, 29 * ONE_DAY_SEC);
+        assert_eq!(schedule.unlocked_balance(february - 1), 0);
+        assert_eq!(schedule.unlocked_balance(february), TOTAL / 48);
+        assert_eq!(schedule.unlocked_balance(march - 1), TOTAL / 48);
+        assert_eq!(schedule.unlocked_balance(march), TOTAL / 24);
+        assert_eq!(schedule.unlocked_balance(start.add_months(48).timestamp()), TOTAL);
+    }
+
+    #[test]
+    fn immediate_and_linear() {
+        let schedule = Schedule::new_immediate_and_linear(TOTAL, START, 25, ONE_YEAR_SEC);
+        schedule.assert_valid(TOTAL);
+
+        assert_eq!(schedule.unlocked_balance(START - 1), 0);
+        assert_eq!(schedule.unlocked_balance(START), TOTAL / 4);
+        assert_eq!(schedule.unlocked_balance(START + ONE_YEAR_SEC), TOTAL);
+
+        for immediate_percent in [0, 100] {
+            Schedule::new_immediate_and_linear(TOTAL, START, immediate_percent, ONE_YEAR_SEC).assert_valid(TOTAL);
+        }
+    }
+}
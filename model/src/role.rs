@@ -0,0 +1,27 @@
+use near_sdk::near;
+
+/// A set of permissions that is granted to accounts.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Grants and revokes roles, manages the token whitelist, the relayer fee limit and the termination grace period
+    Admin,
+    /// Creates lockups and funds draft groups
+    LockupCreator,
+    /// Terminates, reduces and pauses lockups without their own terminator, approves lockup transfers
+    Terminator,
+    /// Manages drafts and draft groups
+    DraftOperator,
+    /// Pauses and unpauses operations of the contract
+    Pauser,
+    /// Updates the code of the contract
+    Updater,
+    /// Claims on behalf of lockup owners for a fee limited by the admins
+    Relayer,
+}
+
+impl Role {
+    /// The roles that give what a member of the former deposit whitelist could do.
+    pub const DEPOSIT_WHITELIST: [Role; 4] = [Role::Admin, Role::LockupCreator, Role::Terminator, Role::DraftOperator];
+}
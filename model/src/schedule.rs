@@ -1,6 +1,28 @@
+use std::borrow::Cow;
+
 use near_sdk::{borsh::to_vec, env, near, CryptoHash};
 
-use crate::{u256::U256, util::u128_dec_format, Balance, TimestampSec};
+use crate::{calendar::CalendarCheckpoint, u256::U256, util::u128_dec_format, Balance, TimestampSec};
+
+/// Defines how the unlocked balance moves from the previous checkpoint to the next one.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// The balance grows linearly between the checkpoints.
+    #[default]
+    Linear,
+    /// The balance of the checkpoint is released right after the previous checkpoint.
+    StepAtStart,
+    /// The balance of the previous checkpoint is kept until the checkpoint.
+    StepAtEnd,
+}
+
+impl Interpolation {
+    pub fn is_linear(&self) -> bool {
+        *self == Self::Linear
+    }
+}
 
 #[near(serializers=[borsh, json])]
 #[derive(Clone, Debug, PartialEq)]
@@ -9,38 +31,168 @@ pub struct Checkpoint {
     pub timestamp: TimestampSec,
     #[serde(with = "u128_dec_format")]
     pub balance: Balance,
+    /// How the balance changes between the previous checkpoint and this one.
+    /// Ignored for the first checkpoint.
+    #[serde(default, skip_serializing_if = "Interpolation::is_linear")]
+    pub interpolation: Interpolation,
+}
+
+/// The maximum number of steps of a parametric schedule, so its expansion fits into the gas of a view.
+pub const MAX_SCHEDULE_STEPS: u32 = 1_000;
+
+/// A step schedule stored as its parameters instead of the explicit list of checkpoints.
+/// `total_balance` is released in `count` equal steps, one every `period` seconds after `start`.
+/// Nothing is unlocked before `start + cliff`, the steps passed by then are released at once.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParametricSchedule {
+    pub start: TimestampSec,
+    pub cliff: TimestampSec,
+    pub period: TimestampSec,
+    pub count: u32,
+    #[serde(with = "u128_dec_format")]
+    pub total_balance: Balance,
+}
+
+impl ParametricSchedule {
+    pub fn assert_valid(&self, total_balance: Balance) {
+        assert!(self.period > 0, "The schedule period should be positive");
+        assert!(self.count > 0, "The schedule steps count should be positive");
+        assert!(
+            self.count <= MAX_SCHEDULE_STEPS,
+            "The schedule steps count should not exceed {MAX_SCHEDULE_STEPS}"
+        );
+        self.period
+            .checked_mul(self.count)
+            .and_then(|duration| self.start.checked_add(duration.max(self.cliff)))
+            .expect("The schedule finish timestamp overflows");
+        assert!(self.total_balance > 0, "expected total balance to be positive",);
+        assert_eq!(
+            self.total_balance, total_balance,
+            "The schedule's total balance doesn't match the transferred balance"
+        );
+    }
+
+    pub fn unlocked_balance(&self, current_timestamp: TimestampSec) -> Balance {
+        if current_timestamp < self.start.saturating_add(self.cliff) {
+            return 0;
+        }
+        let steps = ((current_timestamp - self.start) / self.period).min(self.count);
+        self.step_balance(steps)
+    }
+
+    pub fn finish_timestamp(&self) -> TimestampSec {
+        self.start + (self.period * self.count).max(self.cliff)
+    }
+
+    /// Expands the schedule into the equivalent list of checkpoints.
+    pub fn checkpoints(&self) -> Vec<Checkpoint> {
+        let mut checkpoints = vec![Checkpoint {
+            timestamp: self.start,
+            balance: 0,
+            interpolation: Interpolation::Linear,
+        }];
+        let cliff_steps = (self.cliff / self.period).min(self.count);
+        if cliff_steps > 0 {
+            push_step(
+                &mut checkpoints,
+                self.start + self.cliff,
+                self.step_balance(cliff_steps),
+            );
+        }
+        for step in cliff_steps + 1..=self.count {
+            push_step(
+                &mut checkpoints,
+                self.start + self.period * step,
+                self.step_balance(step),
+            );
+        }
+
+        checkpoints
+    }
+
+    fn step_balance(&self, steps: u32) -> Balance {
+        (U256::from(self.total_balance) * U256::from(steps) / U256::from(self.count)).as_u128()
+    }
+}
+
+/// Appends a checkpoint that keeps the previous balance until `timestamp` and switches to `balance` at it.
+fn push_step(checkpoints: &mut Vec<Checkpoint>, timestamp: TimestampSec, balance: Balance) {
+    checkpoints.push(Checkpoint {
+        timestamp,
+        balance,
+        interpolation: Interpolation::StepAtEnd,
+    });
+}
+
+/// Computes `balance * numerator / denominator` rounding down.
+pub(crate) fn proportion(balance: Balance, numerator: Balance, denominator: Balance) -> Balance {
+    (U256::from(balance) * U256::from(numerator) / U256::from(denominator)).as_u128()
+}
+
+/// Splits `amount` proportionally to `weights` that sum up to `weights_total`, so the parts sum up to `amount`.
+/// The parts are rounded down, then the rest is added one by one to the parts that were rounded.
+pub(crate) fn split_proportionally(amount: Balance, weights: &[Balance], weights_total: Balance) -> Vec<Balance> {
+    let mut parts: Vec<Balance> = weights
+        .iter()
+        .map(|&weight| proportion(amount, weight, weights_total))
+        .collect();
+    let mut rest = amount - parts.iter().sum::<Balance>();
+    for (part, &weight) in parts.iter_mut().zip(weights) {
+        if rest == 0 {
+            break;
+        }
+        if !(U256::from(amount) * U256::from(weight) % U256::from(weights_total)).is_zero() {
+            *part += 1;
+            rest -= 1;
+        }
+    }
+    assert_eq!(rest, 0, "Invariant");
+
+    parts
 }
 
 #[near(serializers=[borsh, json])]
 #[derive(Clone, Debug, PartialEq)]
-pub struct Schedule(pub Vec<Checkpoint>);
+#[serde(untagged)]
+pub enum Schedule {
+    Checkpoints(Vec<Checkpoint>),
+    Parametric(ParametricSchedule),
+    /// Checkpoints on UTC calendar dates. The balance of a checkpoint is released at once
+    /// at the beginning of its date and stays unchanged until the next date.
+    Calendar(Vec<CalendarCheckpoint>),
+}
 
 impl Schedule {
     pub fn new_zero_balance_from_to(start_timestamp: TimestampSec, finish_timestamp: TimestampSec) -> Self {
         assert!(finish_timestamp > start_timestamp, "Invariant");
 
-        Self(vec![
+        Self::Checkpoints(vec![
             Checkpoint {
                 timestamp: start_timestamp,
                 balance: 0,
+                interpolation: Interpolation::Linear,
             },
             Checkpoint {
                 timestamp: finish_timestamp,
                 balance: 0,
+                interpolation: Interpolation::Linear,
             },
         ])
     }
 
     pub fn new_unlocked_since(total_balance: Balance, timestamp: TimestampSec) -> Self {
         assert!(timestamp > 0, "Invariant");
-        Self(vec![
+        Self::Checkpoints(vec![
             Checkpoint {
                 timestamp: timestamp - 1,
                 balance: 0,
+                interpolation: Interpolation::Linear,
             },
             Checkpoint {
                 timestamp,
                 balance: total_balance,
+                interpolation: Interpolation::Linear,
             },
         ])
     }
@@ -50,21 +202,86 @@ impl Schedule {
         Self::new_unlocked_since(total_balance, 1)
     }
 
+    /// Returns the checkpoints of the schedule, expanding a parametric or calendar schedule if needed.
+    pub fn checkpoints(&self) -> Cow<'_, [Checkpoint]> {
+        match self {
+            Self::Checkpoints(checkpoints) => Cow::Borrowed(checkpoints),
+            Self::Parametric(schedule) => Cow::Owned(schedule.checkpoints()),
+            Self::Calendar(checkpoints) => {
+                let mut expanded = vec![Checkpoint {
+                    timestamp: checkpoints.first().unwrap().date.timestamp(),
+                    balance: checkpoints.first().unwrap().balance,
+                    interpolation: Interpolation::Linear,
+                }];
+                for checkpoint in &checkpoints[1..] {
+                    push_step(&mut expanded, checkpoint.date.timestamp(), checkpoint.balance);
+                }
+                Cow::Owned(expanded)
+            }
+        }
+    }
+
+    /// Converts the schedule into the explicit list of checkpoints in place, so it can be modified.
+    pub fn make_checkpoints(&mut self) -> &mut Vec<Checkpoint> {
+        if !matches!(self, Self::Checkpoints(_)) {
+            let checkpoints = self.checkpoints().into_owned();
+            *self = Self::Checkpoints(checkpoints);
+        }
+        match self {
+            Self::Checkpoints(checkpoints) => checkpoints,
+            Self::Parametric(_) | Self::Calendar(_) => unreachable!(),
+        }
+    }
+
+    pub fn start_timestamp(&self) -> TimestampSec {
+        match self {
+            Self::Checkpoints(checkpoints) => checkpoints.first().unwrap().timestamp,
+            Self::Parametric(schedule) => schedule.start,
+            Self::Calendar(checkpoints) => checkpoints.first().unwrap().date.timestamp(),
+        }
+    }
+
+    pub fn finish_timestamp(&self) -> TimestampSec {
+        match self {
+            Self::Checkpoints(checkpoints) => checkpoints.last().unwrap().timestamp,
+            Self::Parametric(schedule) => schedule.finish_timestamp(),
+            Self::Calendar(checkpoints) => checkpoints.last().unwrap().date.timestamp(),
+        }
+    }
+
     pub fn assert_valid(&self, total_balance: Balance) {
-        assert!(self.0.len() >= 2, "At least two checkpoints is required");
+        match self {
+            Self::Checkpoints(_) => {}
+            Self::Parametric(schedule) => return schedule.assert_valid(total_balance),
+            Self::Calendar(checkpoints) => {
+                assert!(checkpoints.len() >= 2, "At least two checkpoints is required");
+                for checkpoint in checkpoints {
+                    checkpoint.date.assert_valid();
+                }
+                for i in 1..checkpoints.len() {
+                    assert!(
+                        checkpoints[i - 1].date < checkpoints[i].date,
+                        "The date of checkpoint #{} should be earlier than the date of the next checkpoint",
+                        i - 1
+                    );
+                }
+            }
+        }
+        let checkpoints = self.checkpoints();
+        assert!(checkpoints.len() >= 2, "At least two checkpoints is required");
         assert_eq!(
-            self.0.first().unwrap().balance,
+            checkpoints.first().unwrap().balance,
             0,
             "The first checkpoint balance should be 0"
         );
-        for i in 1..self.0.len() {
+        for i in 1..checkpoints.len() {
             assert!(
-                self.0[i - 1].timestamp < self.0[i].timestamp,
+                checkpoints[i - 1].timestamp < checkpoints[i].timestamp,
                 "The timestamp of checkpoint #{} should be less than the timestamp of the next checkpoint",
                 i - 1
             );
             assert!(
-                self.0[i - 1].balance <= self.0[i].balance,
+                checkpoints[i - 1].balance <= checkpoints[i].balance,
                 "The balance of checkpoint #{} should be not greater than the balance of the next checkpoint",
                 i - 1
             );
@@ -80,28 +297,48 @@ impl Schedule {
     /// Verifies that this schedule is ahead of the given termination schedule at any point of time.
     /// Assumes they have equal total balance and both schedules are valid.
     pub fn assert_valid_termination_schedule(&self, termination_schedule: &Schedule) {
-        for checkpoint in &self.0 {
+        // Both schedules change monotonically between their critical timestamps,
+        // so comparing them at the critical timestamps of either is enough.
+        for timestamp in self
+            .critical_timestamps()
+            .into_iter()
+            .chain(termination_schedule.critical_timestamps())
+        {
             assert!(
-                checkpoint.balance <= termination_schedule.unlocked_balance(checkpoint.timestamp),
+                self.unlocked_balance(timestamp) <= termination_schedule.unlocked_balance(timestamp),
                 "The lockup schedule is ahead of the termination schedule at timestamp {}",
-                checkpoint.timestamp
+                timestamp
             );
         }
-        for checkpoint in &termination_schedule.0 {
-            assert!(
-                checkpoint.balance >= self.unlocked_balance(checkpoint.timestamp),
-                "The lockup schedule is ahead of the termination schedule at timestamp {}",
-                checkpoint.timestamp
-            );
+    }
+
+    /// Timestamps of the checkpoints along with the edges of the steps between them.
+    fn critical_timestamps(&self) -> Vec<TimestampSec> {
+        let checkpoints = self.checkpoints();
+        let mut timestamps = vec![checkpoints.first().unwrap().timestamp];
+        for pair in checkpoints.windows(2) {
+            match pair[1].interpolation {
+                Interpolation::Linear => {}
+                Interpolation::StepAtStart => timestamps.push(pair[0].timestamp + 1),
+                Interpolation::StepAtEnd => timestamps.push(pair[1].timestamp - 1),
+            }
+            timestamps.push(pair[1].timestamp);
         }
+
+        timestamps
     }
 
     pub fn unlocked_balance(&self, current_timestamp: TimestampSec) -> Balance {
+        let checkpoints = match self {
+            Self::Checkpoints(checkpoints) => checkpoints,
+            Self::Parametric(schedule) => return schedule.unlocked_balance(current_timestamp),
+            Self::Calendar(checkpoints) => {
+                let passed = checkpoints.partition_point(|checkpoint| checkpoint.date.timestamp() <= current_timestamp);
+                return passed.checked_sub(1).map_or(0, |index| checkpoints[index].balance);
+            }
+        };
         // Using binary search by time to find the current checkpoint.
-        let index = match self
-            .0
-            .binary_search_by_key(&current_timestamp, |checkpoint| checkpoint.timestamp)
-        {
+        let index = match checkpoints.binary_search_by_key(&current_timestamp, |checkpoint| checkpoint.timestamp) {
             // Exact timestamp found
             Ok(index) => index,
             // No match, the next index is given.
@@ -113,23 +350,33 @@ impl Schedule {
                 index - 1
             }
         };
-        let checkpoint = &self.0[index];
-        if index + 1 == self.0.len() {
+        let checkpoint = &checkpoints[index];
+        if index + 1 == checkpoints.len() {
             // The last checkpoint. Fully unlocked.
             return checkpoint.balance;
         }
-        let next_checkpoint = &self.0[index + 1];
+        let next_checkpoint = &checkpoints[index + 1];
 
-        let total_duration = next_checkpoint.timestamp - checkpoint.timestamp;
         let passed_duration = current_timestamp - checkpoint.timestamp;
-        checkpoint.balance
-            + (U256::from(passed_duration) * U256::from(next_checkpoint.balance - checkpoint.balance)
-                / U256::from(total_duration))
-            .as_u128()
+        match next_checkpoint.interpolation {
+            Interpolation::Linear => {
+                let total_duration = next_checkpoint.timestamp - checkpoint.timestamp;
+                checkpoint.balance
+                    + (U256::from(passed_duration) * U256::from(next_checkpoint.balance - checkpoint.balance)
+                        / U256::from(total_duration))
+                    .as_u128()
+            }
+            Interpolation::StepAtStart if passed_duration > 0 => next_checkpoint.balance,
+            Interpolation::StepAtStart | Interpolation::StepAtEnd => checkpoint.balance,
+        }
     }
 
     pub fn total_balance(&self) -> Balance {
-        self.0.last().unwrap().balance
+        match self {
+            Self::Checkpoints(checkpoints) => checkpoints.last().unwrap().balance,
+            Self::Parametric(schedule) => schedule.total_balance,
+            Self::Calendar(checkpoints) => checkpoints.last().unwrap().balance,
+        }
     }
 
     /// Terminates the lockup schedule earlier.
@@ -139,30 +386,49 @@ impl Schedule {
             // finish_timestamp is a hint, only used for fully unvested schedules
             // can be overwritten to preserve schedule invariants
             // used to preserve part of the schedule before the termination happens
-            let start_timestamp = self.0[0].timestamp;
+            let start_timestamp = self.start_timestamp();
             let finish_timestamp = if finish_timestamp > start_timestamp {
                 finish_timestamp
             } else {
                 start_timestamp + 1
             };
-            self.0 = Self::new_zero_balance_from_to(start_timestamp, finish_timestamp).0;
+            *self = Self::new_zero_balance_from_to(start_timestamp, finish_timestamp);
             return;
         }
-        assert!(new_total_balance <= self.0.last().unwrap().balance, "Invariant");
-        while let Some(checkpoint) = self.0.pop() {
-            if self.0.last().unwrap().balance < new_total_balance {
-                let prev_checkpoint = self.0.last().unwrap().clone();
-                let timestamp_diff = checkpoint.timestamp - prev_checkpoint.timestamp;
-                let balance_diff = checkpoint.balance - prev_checkpoint.balance;
-                let required_balance_diff = new_total_balance - prev_checkpoint.balance;
-                // Computing the new timestamp rounding up
-                let new_timestamp = prev_checkpoint.timestamp
-                    + ((U256::from(timestamp_diff) * U256::from(required_balance_diff) + U256::from(balance_diff - 1))
-                        / U256::from(balance_diff))
-                    .as_u32();
-                self.0.push(Checkpoint {
+        assert!(new_total_balance <= self.total_balance(), "Invariant");
+        if let Self::Calendar(checkpoints) = self {
+            // Rounding up to the calendar date when the remaining balance is reached
+            let index = checkpoints
+                .iter()
+                .position(|checkpoint| checkpoint.balance >= new_total_balance)
+                .unwrap();
+            checkpoints.truncate(index + 1);
+            checkpoints[index].balance = new_total_balance;
+            return;
+        }
+        let checkpoints = self.make_checkpoints();
+        while let Some(checkpoint) = checkpoints.pop() {
+            if checkpoints.last().unwrap().balance < new_total_balance {
+                let prev_checkpoint = checkpoints.last().unwrap().clone();
+                let new_timestamp = match checkpoint.interpolation {
+                    Interpolation::Linear => {
+                        let timestamp_diff = checkpoint.timestamp - prev_checkpoint.timestamp;
+                        let balance_diff = checkpoint.balance - prev_checkpoint.balance;
+                        let required_balance_diff = new_total_balance - prev_checkpoint.balance;
+                        // Computing the new timestamp rounding up
+                        prev_checkpoint.timestamp
+                            + ((U256::from(timestamp_diff) * U256::from(required_balance_diff)
+                                + U256::from(balance_diff - 1))
+                                / U256::from(balance_diff))
+                            .as_u32()
+                    }
+                    Interpolation::StepAtStart => prev_checkpoint.timestamp + 1,
+                    Interpolation::StepAtEnd => checkpoint.timestamp,
+                };
+                checkpoints.push(Checkpoint {
                     timestamp: new_timestamp,
                     balance: new_total_balance,
+                    interpolation: checkpoint.interpolation,
                 });
                 return;
             }
@@ -170,11 +436,487 @@ impl Schedule {
         unreachable!();
     }
 
+    /// Combines the schedules into one that unlocks the sum of their balances.
+    /// The balance unlocked at `timestamp` is merged exactly, the balances between the checkpoints of
+    /// the linear parts are rounded, so the merged schedule may differ from the sum by a few units.
+    pub fn merge(schedules: &[&Schedule], timestamp: TimestampSec) -> Self {
+        let mut timestamps: Vec<TimestampSec> = schedules
+            .iter()
+            .flat_map(|schedule| schedule.critical_timestamps())
+            .chain(std::iter::once(timestamp))
+            .collect();
+        timestamps.sort_unstable();
+        timestamps.dedup();
+
+        Self::Checkpoints(
+            timestamps
+                .into_iter()
+                .map(|timestamp| Checkpoint {
+                    timestamp,
+                    balance: schedules
+                        .iter()
+                        .map(|schedule| schedule.unlocked_balance(timestamp))
+                        .sum(),
+                    interpolation: Interpolation::Linear,
+                })
+                .collect(),
+        )
+    }
+
+    /// Splits the schedule into schedules with the given total balances, which sum up to the total balance of it.
+    /// The balance unlocked at `timestamp` is split exactly. Balances of the other checkpoints are rounded,
+    /// so the sum of the schedules may differ from this one by a few units.
+    pub fn split(&self, total_balances: &[Balance], timestamp: TimestampSec) -> Vec<Schedule> {
+        let total_balance = self.total_balance();
+        assert_eq!(total_balances.iter().sum::<Balance>(), total_balance, "Invariant");
+
+        let (checkpoints, pivot) = self.checkpoints_with_pivot(timestamp);
+        let unlocked_balance = pivot.map_or(0, |index| checkpoints[index].balance);
+        let unlocked_parts = split_proportionally(unlocked_balance, total_balances, total_balance);
+
+        total_balances
+            .iter()
+            .zip(unlocked_parts)
+            .map(|(&part_total_balance, unlocked_part)| {
+                let checkpoints = checkpoints
+                    .iter()
+                    .enumerate()
+                    .map(|(index, checkpoint)| {
+                        let balance = proportion(checkpoint.balance, part_total_balance, total_balance);
+                        Checkpoint {
+                            timestamp: checkpoint.timestamp,
+                            balance: match pivot {
+                                Some(pivot) if index == pivot => unlocked_part,
+                                Some(pivot) if index > pivot => balance.max(unlocked_part),
+                                _ => balance,
+                            },
+                            interpolation: checkpoint.interpolation,
+                        }
+                    })
+                    .collect();
+                Self::Checkpoints(checkpoints)
+            })
+            .collect()
+    }
+
+    /// Takes `reduction` out of the balance that is still locked at `timestamp`.
+    /// The unlocked balance stays, the locked part keeps unlocking on the same curve scaled down.
+    pub fn reduce(&mut self, reduction: Balance, timestamp: TimestampSec) {
+        let total_balance = self.total_balance();
+        let (checkpoints, pivot) = self.checkpoints_with_pivot(timestamp);
+        let locked_balance = total_balance - pivot.map_or(0, |index| checkpoints[index].balance);
+        assert!(reduction <= locked_balance, "The reduction exceeds the locked balance");
+        if reduction == 0 {
+            return;
+        }
+
+        // The balance remaining to unlock after each checkpoint is scaled rounding up,
+        // so the order of schedules reduced by the same amount is preserved
+        let new_total_balance = total_balance - reduction;
+        let new_locked_balance = U256::from(locked_balance - reduction);
+        let locked_balance = U256::from(locked_balance);
+        let checkpoints = checkpoints
+            .into_iter()
+            .enumerate()
+            .map(|(index, checkpoint)| {
+                if pivot.map_or(false, |pivot| index <= pivot) {
+                    return checkpoint;
+                }
+                let remaining_balance = U256::from(total_balance - checkpoint.balance);
+                let new_remaining_balance =
+                    ((remaining_balance * new_locked_balance + locked_balance - 1) / locked_balance).as_u128();
+                Checkpoint {
+                    balance: new_total_balance - new_remaining_balance,
+                    ..checkpoint
+                }
+            })
+            .collect();
+        *self = Self::Checkpoints(checkpoints);
+    }
+
+    /// Delays the part of the schedule after `timestamp` by `duration`,
+    /// so the balance unlocked at `timestamp` stays unchanged during the delay.
+    pub fn shift(&mut self, timestamp: TimestampSec, duration: TimestampSec) {
+        let (mut checkpoints, pivot) = self.checkpoints_with_pivot(timestamp);
+        if duration == 0 || pivot.map_or(false, |pivot| pivot + 1 == checkpoints.len()) {
+            return;
+        }
+
+        let first_shifted = pivot.map_or(0, |pivot| pivot + 1);
+        for checkpoint in &mut checkpoints[first_shifted..] {
+            checkpoint.timestamp = checkpoint
+                .timestamp
+                .checked_add(duration)
+                .expect("The schedule timestamp overflows");
+        }
+        if let Some(pivot) = pivot {
+            // Keeps the pivot balance during the delay, so the next segment keeps its length
+            let checkpoint = Checkpoint {
+                timestamp: checkpoints[pivot].timestamp + duration,
+                balance: checkpoints[pivot].balance,
+                interpolation: Interpolation::Linear,
+            };
+            checkpoints.insert(pivot + 1, checkpoint);
+        }
+        *self = Self::Checkpoints(checkpoints);
+    }
+
+    /// Returns expanded checkpoints with one inserted at `timestamp` unless it's outside of the schedule,
+    /// and the index of the last checkpoint that is passed by the timestamp.
+    fn checkpoints_with_pivot(&self, timestamp: TimestampSec) -> (Vec<Checkpoint>, Option<usize>) {
+        let mut checkpoints = self.checkpoints().into_owned();
+        let pivot = match checkpoints.binary_search_by_key(&timestamp, |checkpoint| checkpoint.timestamp) {
+            Ok(index) => Some(index),
+            Err(0) => None,
+            Err(index) if index == checkpoints.len() => Some(index - 1),
+            Err(index) => {
+                let checkpoint = Checkpoint {
+                    timestamp,
+                    balance: self.unlocked_balance(timestamp),
+                    interpolation: checkpoints[index].interpolation,
+                };
+                checkpoints.insert(index, checkpoint);
+                Some(index)
+            }
+        };
+        (checkpoints, pivot)
+    }
+
+    /// Hashes the expanded checkpoints, so a parametric schedule has the same hash
+    /// as the equivalent list of checkpoints.
+    /// Linear-only schedules are hashed without interpolation to keep hashes created before it was introduced.
     pub fn hash(&self) -> CryptoHash {
-        let value_hash = env::sha256(&to_vec(self).unwrap());
+        let checkpoints = self.checkpoints();
+        let value = if checkpoints
+            .iter()
+            .all(|checkpoint| checkpoint.interpolation.is_linear())
+        {
+            let legacy: Vec<(TimestampSec, Balance)> = checkpoints
+                .iter()
+                .map(|checkpoint| (checkpoint.timestamp, checkpoint.balance))
+                .collect();
+            to_vec(&legacy)
+        } else {
+            to_vec(&*checkpoints)
+        };
+        let value_hash = env::sha256(&value.unwrap());
         let mut res = CryptoHash::default();
         res.copy_from_slice(&value_hash);
 
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::CalendarDate;
+
+    const START: TimestampSec = 1_600_000_000;
+    const PERIOD: TimestampSec = 1_000;
+
+    fn parametric(cliff: TimestampSec, count: u32) -> ParametricSchedule {
+        ParametricSchedule {
+            start: START,
+            cliff,
+            period: PERIOD,
+            count,
+            total_balance: 12_000,
+        }
+    }
+
+    fn checkpoint(timestamp: TimestampSec, balance: Balance, interpolation: Interpolation) -> Checkpoint {
+        Checkpoint {
+            timestamp,
+            balance,
+            interpolation,
+        }
+    }
+
+    #[test]
+    fn step_interpolation() {
+        let mut schedule = Schedule::Checkpoints(vec![
+            checkpoint(START, 0, Interpolation::Linear),
+            checkpoint(START + PERIOD, 1_000, Interpolation::StepAtStart),
+            checkpoint(START + PERIOD * 2, 2_000, Interpolation::StepAtEnd),
+            checkpoint(START + PERIOD * 3, 3_000, Interpolation::Linear),
+        ]);
+        schedule.assert_valid(3_000);
+
+        assert_eq!(schedule.unlocked_balance(START), 0);
+        assert_eq!(schedule.unlocked_balance(START + 1), 1_000);
+        assert_eq!(schedule.unlocked_balance(START + PERIOD * 2 - 1), 1_000);
+        assert_eq!(schedule.unlocked_balance(START + PERIOD * 2), 2_000);
+        assert_eq!(schedule.unlocked_balance(START + PERIOD * 2 + PERIOD / 2), 2_500);
+
+        let linear = Schedule::Checkpoints(vec![
+            checkpoint(START, 0, Interpolation::Linear),
+            checkpoint(START + PERIOD * 3, 3_000, Interpolation::Linear),
+        ]);
+        linear.assert_valid_termination_schedule(&schedule);
+
+        schedule.terminate(1_500, START + PERIOD);
+        schedule.assert_valid(1_500);
+        assert_eq!(schedule.unlocked_balance(START + PERIOD * 2 - 1), 1_000);
+        assert_eq!(schedule.finish_timestamp(), START + PERIOD * 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The lockup schedule is ahead of the termination schedule")]
+    fn step_ahead_of_termination_schedule() {
+        let schedule = Schedule::Checkpoints(vec![
+            checkpoint(START, 0, Interpolation::Linear),
+            checkpoint(START + PERIOD, 1_000, Interpolation::StepAtStart),
+        ]);
+        let termination_schedule = Schedule::Checkpoints(vec![
+            checkpoint(START, 0, Interpolation::Linear),
+            checkpoint(START + PERIOD, 1_000, Interpolation::Linear),
+        ]);
+
+        schedule.assert_valid_termination_schedule(&termination_schedule);
+    }
+
+    #[test]
+    fn split() {
+        let schedule = Schedule::Parametric(parametric(0, 12));
+        let timestamp = START + PERIOD * 5 + 10;
+        let parts = schedule.split(&[7_001, 4_999], timestamp);
+
+        parts[0].assert_valid(7_001);
+        parts[1].assert_valid(4_999);
+        assert_eq!(
+            parts[0].unlocked_balance(timestamp) + parts[1].unlocked_balance(timestamp),
+            schedule.unlocked_balance(timestamp)
+        );
+        for timestamp in (START..=schedule.finish_timestamp()).step_by(100) {
+            let sum = parts[0].unlocked_balance(timestamp) + parts[1].unlocked_balance(timestamp);
+            assert!(sum.abs_diff(schedule.unlocked_balance(timestamp)) <= 2);
+        }
+    }
+
+    #[test]
+    fn reduce() {
+        let schedule = Schedule::Parametric(parametric(PERIOD * 3, 12));
+        let timestamp = START + PERIOD * 5 + 10;
+        let unlocked_balance = schedule.unlocked_balance(timestamp);
+
+        let mut reduced = schedule.clone();
+        reduced.reduce(3_500, timestamp);
+        reduced.assert_valid(8_500);
+        for timestamp in START - 1..=timestamp {
+            assert_eq!(
+                reduced.unlocked_balance(timestamp),
+                schedule.unlocked_balance(timestamp)
+            );
+        }
+        let locked_balance = 12_000 - unlocked_balance;
+        for timestamp in (timestamp..=schedule.finish_timestamp()).step_by(100) {
+            let expected = unlocked_balance
+                + (schedule.unlocked_balance(timestamp) - unlocked_balance) * (locked_balance - 3_500) / locked_balance;
+            assert!(
+                reduced.unlocked_balance(timestamp).abs_diff(expected) <= 1,
+                "at {timestamp}"
+            );
+        }
+
+        let mut vesting_schedule = Schedule::Checkpoints(vec![
+            checkpoint(START, 0, Interpolation::Linear),
+            checkpoint(START + PERIOD * 12, 12_000, Interpolation::Linear),
+        ]);
+        vesting_schedule.reduce(3_500, timestamp);
+        reduced.assert_valid_termination_schedule(&vesting_schedule);
+
+        let mut terminated = schedule.clone();
+        terminated.reduce(locked_balance, timestamp);
+        terminated.assert_valid(unlocked_balance);
+    }
+
+    #[test]
+    fn shift() {
+        let schedule = Schedule::Checkpoints(vec![
+            checkpoint(START, 0, Interpolation::Linear),
+            checkpoint(START + PERIOD, 1_000, Interpolation::StepAtStart),
+            checkpoint(START + PERIOD * 3, 3_000, Interpolation::Linear),
+        ]);
+        let timestamp = START + PERIOD * 2;
+        let duration = PERIOD / 2;
+
+        let mut shifted = schedule.clone();
+        shifted.shift(timestamp, duration);
+        shifted.assert_valid(3_000);
+        for timestamp in START - 1..=timestamp {
+            assert_eq!(
+                shifted.unlocked_balance(timestamp),
+                schedule.unlocked_balance(timestamp)
+            );
+        }
+        for timestamp in timestamp..=timestamp + duration {
+            assert_eq!(shifted.unlocked_balance(timestamp), 2_000);
+        }
+        for timestamp in (timestamp..=schedule.finish_timestamp() + PERIOD).step_by(100) {
+            assert_eq!(
+                shifted.unlocked_balance(timestamp + duration),
+                schedule.unlocked_balance(timestamp)
+            );
+        }
+
+        let mut not_started = schedule.clone();
+        not_started.shift(START - PERIOD, duration);
+        assert_eq!(not_started.start_timestamp(), START + duration);
+
+        let mut finished = schedule.clone();
+        finished.shift(schedule.finish_timestamp() + 1, duration);
+        assert_eq!(finished, schedule);
+    }
+
+    #[test]
+    fn merge() {
+        let schedules = [
+            Schedule::Parametric(parametric(PERIOD * 3, 12)),
+            Schedule::new_unlocked_since(5_000, START + PERIOD / 2),
+            Schedule::Checkpoints(vec![
+                checkpoint(START + PERIOD, 0, Interpolation::Linear),
+                checkpoint(START + PERIOD * 7, 3_000, Interpolation::Linear),
+            ]),
+        ];
+        let merged = Schedule::merge(&schedules.iter().collect::<Vec<_>>(), START + PERIOD * 5 + 10);
+        merged.assert_valid(20_000);
+
+        for timestamp in START - 1..=START + PERIOD * 13 {
+            let sum: Balance = schedules
+                .iter()
+                .map(|schedule| schedule.unlocked_balance(timestamp))
+                .sum();
+            assert!(sum.abs_diff(merged.unlocked_balance(timestamp)) <= 1, "at {timestamp}");
+        }
+    }
+
+    #[test]
+    fn merge_mixed_interpolations() {
+        let dates: Vec<_> = (0..=3)
+            .map(|month| CalendarDate {
+                year: 2025,
+                month: 1 + month,
+                day: 1,
+            })
+            .collect();
+        let start = dates[0].timestamp();
+        let schedules = [
+            Schedule::Checkpoints(vec![
+                checkpoint(start, 0, Interpolation::Linear),
+                checkpoint(start + PERIOD, 1_000, Interpolation::StepAtStart),
+                checkpoint(start + PERIOD * 3, 3_000, Interpolation::StepAtEnd),
+            ]),
+            Schedule::Calendar(
+                dates
+                    .iter()
+                    .zip([0, 1_000, 2_000, 3_000])
+                    .map(|(&date, balance)| CalendarCheckpoint { date, balance })
+                    .collect(),
+            ),
+            Schedule::Checkpoints(vec![
+                checkpoint(start + PERIOD / 2, 0, Interpolation::Linear),
+                checkpoint(start + PERIOD * 5, 7_000, Interpolation::Linear),
+            ]),
+        ];
+        let timestamp = start + PERIOD * 2 + 333;
+        let merged = Schedule::merge(&schedules.iter().collect::<Vec<_>>(), timestamp);
+        merged.assert_valid(13_000);
+
+        let sum_at = |timestamp: TimestampSec| -> Balance {
+            schedules
+                .iter()
+                .map(|schedule| schedule.unlocked_balance(timestamp))
+                .sum()
+        };
+        assert_eq!(sum_at(timestamp), merged.unlocked_balance(timestamp));
+        // the steps are kept exactly, only the linear part is rounded
+        let mut timestamps: Vec<TimestampSec> = (start - 1..=start + PERIOD * 6).collect();
+        timestamps.extend(dates.iter().flat_map(|date| [date.timestamp() - 1, date.timestamp()]));
+        for timestamp in timestamps {
+            assert!(
+                sum_at(timestamp).abs_diff(merged.unlocked_balance(timestamp)) <= 1,
+                "at {timestamp}"
+            );
+        }
+        for step in [start + 1, start + PERIOD * 3 - 1, start + PERIOD * 3] {
+            assert_eq!(
+                sum_at(step) - sum_at(step - 1),
+                merged.unlocked_balance(step) - merged.unlocked_balance(step - 1),
+                "at {step}"
+            );
+        }
+    }
+
+    #[test]
+    fn parametric_matches_expansion() {
+        for (cliff, count) in [
+            (0, 12),
+            (PERIOD * 3, 12),
+            (PERIOD * 3 + 500, 12),
+            (PERIOD * 20, 12),
+            (1, 1),
+        ] {
+            let parametric = parametric(cliff, count);
+            parametric.assert_valid(12_000);
+            let expanded = Schedule::Checkpoints(parametric.checkpoints());
+            expanded.assert_valid(12_000);
+
+            assert_eq!(expanded.finish_timestamp(), parametric.finish_timestamp());
+            for timestamp in (START - 1..=parametric.finish_timestamp() + 1).step_by(50) {
+                assert_eq!(
+                    expanded.unlocked_balance(timestamp),
+                    parametric.unlocked_balance(timestamp),
+                    "at {timestamp} with cliff {cliff}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The schedule steps count should not exceed 1000")]
+    fn parametric_steps_limit() {
+        let mut schedule = parametric(0, MAX_SCHEDULE_STEPS);
+        schedule.assert_valid(12_000);
+
+        schedule.count += 1;
+        schedule.assert_valid(12_000);
+    }
+
+    #[test]
+    fn terminate_parametric() {
+        let mut schedule = Schedule::Parametric(parametric(PERIOD * 3, 12));
+        schedule.terminate(6_000, START + PERIOD * 6);
+
+        assert!(matches!(schedule, Schedule::Checkpoints(_)));
+        schedule.assert_valid(6_000);
+        assert_eq!(schedule.unlocked_balance(START + PERIOD * 3), 3_000);
+        assert_eq!(schedule.finish_timestamp(), START + PERIOD * 6);
+    }
+
+    #[test]
+    fn terminate_calendar_rounds_to_date() {
+        let dates: Vec<_> = (0..=4)
+            .map(|month| CalendarDate {
+                year: 2025,
+                month: 1 + month,
+                day: 1,
+            })
+            .collect();
+        let mut schedule = Schedule::Calendar(
+            dates
+                .iter()
+                .zip([0, 1_000, 2_000, 3_000, 4_000])
+                .map(|(&date, balance)| CalendarCheckpoint { date, balance })
+                .collect(),
+        );
+        schedule.assert_valid(4_000);
+
+        schedule.terminate(1_500, dates[1].timestamp() + 10);
+
+        schedule.assert_valid(1_500);
+        assert_eq!(schedule.finish_timestamp(), dates[2].timestamp());
+        assert_eq!(schedule.unlocked_balance(dates[2].timestamp() - 1), 1_000);
+        assert_eq!(schedule.unlocked_balance(dates[2].timestamp()), 1_500);
+    }
+}
@@ -0,0 +1,47 @@
+use near_sdk::{json_types::U128, near, AccountId, StorageUsage};
+use nitka::make_integration_version;
+
+/// The storage an entry of the contract was paid for and the account that paid for it.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoragePayment {
+    pub payer_id: AccountId,
+    /// The bytes that are refunded to the payer once they are freed
+    pub bytes: StorageUsage,
+}
+
+/// NEP-145 storage balance, `available` is the part of `total` that is not spent on storage.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[near(serializers=[json])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+/// Lockup creators and draft operators pay for the storage of lockups and drafts they create
+/// from their storage balances, the freed storage of finished lockups and deleted drafts is refunded there.
+/// Owners pay the same way for lockups they create by splits and merges.
+#[make_integration_version]
+pub trait StorageApi {
+    /// Adds the attached deposit to the storage balance of `account_id` or the predecessor.
+    fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance;
+
+    /// Withdraws `amount` or the whole available balance of the predecessor. Requires one yoctoNEAR.
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+
+    /// Withdraws the available balance and removes the storage balance of the predecessor.
+    /// An account that still pays for storage is unregistered only with `force`, giving up the paid storage.
+    /// Requires one yoctoNEAR.
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool;
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+}
@@ -1,6 +1,10 @@
 use near_sdk::{json_types::Base58CryptoHash, near, AccountId, CryptoHash};
 
-use crate::{lockup::Lockup, schedule::Schedule, Balance, TimestampSec};
+use crate::{
+    lockup::Lockup,
+    schedule::{proportion, Schedule},
+    Balance, TimestampSec, WrappedBalance,
+};
 
 #[near(serializers=[borsh, json])]
 #[derive(Clone, Debug, PartialEq)]
@@ -13,40 +17,272 @@ pub enum VestingConditions {
 #[near(serializers=[borsh, json])]
 #[derive(Debug, PartialEq, Clone)]
 pub struct TerminationConfig {
-    /// The account ID who paid for the lockup creation
-    /// and will receive unvested balance upon termination
+    /// The account ID who will receive unvested balance upon termination,
+    /// the one who paid for the lockup creation unless another refund receiver was specified
     pub beneficiary_id: AccountId,
     /// An optional vesting schedule
     pub vesting_schedule: VestingConditions,
+    /// The account ID who can terminate the lockup, any account with the terminator role if not specified
+    #[serde(default)]
+    pub terminator_id: Option<AccountId>,
+}
+
+/// A termination that was announced and can be executed once its grace period is over.
+#[near(serializers=[borsh, json])]
+#[derive(Debug, PartialEq, Clone)]
+pub struct PendingTermination {
+    /// The vesting stops at this timestamp
+    pub termination_timestamp: TimestampSec,
+    /// The termination can't be executed before this timestamp
+    pub executable_at: TimestampSec,
+}
+
+/// The part of the unvested balance that a partial termination takes back.
+#[near(serializers=[json])]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockupReduction {
+    Amount(WrappedBalance),
+    Percent(u8),
 }
 
 impl Lockup {
+    /// Stops the vesting at `termination_timestamp`, `timestamp` is the current one.
+    /// Returns the unvested balance and the beneficiary.
     pub fn terminate(
         &mut self,
         hashed_schedule: Option<Schedule>,
         termination_timestamp: TimestampSec,
+        timestamp: TimestampSec,
     ) -> (Balance, AccountId) {
+        // the time the lockup was paused before the termination doesn't count for the vesting
+        if self.paused_at.is_some() {
+            self.resume(hashed_schedule.clone(), timestamp.min(termination_timestamp));
+        }
         let termination_config = self.termination_config.take().expect("No termination config");
+        self.pending_termination = None;
+        let total_balance = self.schedule.total_balance();
+        let vested_balance = self
+            .vesting_schedule(&termination_config.vesting_schedule, hashed_schedule.as_ref())
+            .unlocked_balance(termination_timestamp)
+            // a termination executed after its timestamp can't take back what was claimed in between
+            .max(self.claimed_balance);
+        let unvested_balance = total_balance - vested_balance;
+        if unvested_balance > 0 {
+            self.schedule.terminate(vested_balance, termination_timestamp);
+        }
+        (unvested_balance, termination_config.beneficiary_id)
+    }
+
+    /// Takes a part of the unvested balance at `timestamp` back, the rest keeps vesting on the same curve.
+    /// A hashed vesting schedule gets revealed. Returns the taken balance and the beneficiary.
+    pub fn reduce(
+        &mut self,
+        reduction: LockupReduction,
+        hashed_schedule: Option<Schedule>,
+        timestamp: TimestampSec,
+    ) -> (Balance, AccountId) {
+        assert!(
+            self.pending_termination.is_none(),
+            "A lockup with a pending termination can't be reduced"
+        );
+        // a paused lockup is reduced as of the pause, the resume shifts the reduced schedules later
+        let timestamp = self.effective_timestamp(timestamp);
+        let termination_config = self.termination_config.as_ref().expect("No termination config");
+        let vesting_schedule = self.vesting_schedule(&termination_config.vesting_schedule, hashed_schedule.as_ref());
         let total_balance = self.schedule.total_balance();
-        let vested_balance = match &termination_config.vesting_schedule {
+        let unvested_balance = total_balance - vesting_schedule.unlocked_balance(timestamp);
+        let reduced_balance = match reduction {
+            LockupReduction::Amount(amount) => amount.0,
+            LockupReduction::Percent(percent) => {
+                assert!(percent <= 100, "The percent should not exceed 100");
+                proportion(unvested_balance, percent.into(), 100)
+            }
+        };
+        assert!(
+            reduced_balance <= unvested_balance,
+            "The reduction exceeds the unvested balance"
+        );
+        assert!(
+            reduced_balance < total_balance,
+            "The reduction should leave a balance, terminate the lockup instead"
+        );
+
+        let vesting_conditions = match &termination_config.vesting_schedule {
+            VestingConditions::SameAsLockupSchedule => VestingConditions::SameAsLockupSchedule,
+            VestingConditions::Hash(_) | VestingConditions::Schedule(_) => {
+                let mut vesting_schedule = vesting_schedule.clone();
+                vesting_schedule.reduce(reduced_balance, timestamp);
+                VestingConditions::Schedule(vesting_schedule)
+            }
+        };
+        // The lockup schedule is behind the vesting one, so it has at least as much left to unlock
+        self.schedule.reduce(reduced_balance, timestamp);
+        if let VestingConditions::Schedule(vesting_schedule) = &vesting_conditions {
+            self.schedule.assert_valid_termination_schedule(vesting_schedule);
+        }
+
+        let termination_config = self.termination_config.as_mut().unwrap();
+        termination_config.vesting_schedule = vesting_conditions;
+        (reduced_balance, termination_config.beneficiary_id.clone())
+    }
+
+    /// Freezes the unlocking and the vesting of the lockup at `timestamp`.
+    pub fn pause(&mut self, timestamp: TimestampSec) {
+        assert!(
+            self.termination_config.is_some(),
+            "Only a lockup with a vesting schedule can be paused"
+        );
+        assert!(self.paused_at.is_none(), "The lockup is already paused");
+        self.paused_at = Some(timestamp);
+    }
+
+    /// Unfreezes the lockup, the lockup and the vesting schedules after the pause are delayed by its length.
+    /// A hashed vesting schedule gets revealed. Returns the pause duration.
+    pub fn resume(&mut self, hashed_schedule: Option<Schedule>, timestamp: TimestampSec) -> TimestampSec {
+        let paused_at = self.paused_at.take().expect("The lockup is not paused");
+        let duration = timestamp.saturating_sub(paused_at);
+        let termination_config = self.termination_config.as_ref().expect("No termination config");
+        let vesting_conditions = match &termination_config.vesting_schedule {
+            VestingConditions::SameAsLockupSchedule => VestingConditions::SameAsLockupSchedule,
+            VestingConditions::Hash(_) | VestingConditions::Schedule(_) => {
+                let mut vesting_schedule = self
+                    .vesting_schedule(&termination_config.vesting_schedule, hashed_schedule.as_ref())
+                    .clone();
+                vesting_schedule.shift(paused_at, duration);
+                VestingConditions::Schedule(vesting_schedule)
+            }
+        };
+        // Both schedules are delayed the same way, so the lockup schedule stays behind the vesting one
+        self.schedule.shift(paused_at, duration);
+
+        self.termination_config.as_mut().unwrap().vesting_schedule = vesting_conditions;
+        duration
+    }
+
+    /// Returns the vesting schedule, verifying the revealed one if the lockup only has its hash.
+    fn vesting_schedule<'a>(
+        &'a self,
+        vesting_conditions: &'a VestingConditions,
+        hashed_schedule: Option<&'a Schedule>,
+    ) -> &'a Schedule {
+        match vesting_conditions {
             VestingConditions::SameAsLockupSchedule => &self.schedule,
             VestingConditions::Hash(hash) => {
-                let schedule = hashed_schedule
-                    .as_ref()
-                    .expect("Revealed schedule required for the termination");
+                let schedule = hashed_schedule.expect("Revealed schedule required for the termination");
                 let hash: CryptoHash = (*hash).into();
                 assert_eq!(hash, schedule.hash(), "The revealed schedule hash doesn't match");
-                schedule.assert_valid(total_balance);
+                schedule.assert_valid(self.schedule.total_balance());
                 self.schedule.assert_valid_termination_schedule(schedule);
                 schedule
             }
             VestingConditions::Schedule(schedule) => schedule,
         }
-        .unlocked_balance(termination_timestamp);
-        let unvested_balance = total_balance - vested_balance;
-        if unvested_balance > 0 {
-            self.schedule.terminate(vested_balance, termination_timestamp);
-        }
-        (unvested_balance, termination_config.beneficiary_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{Checkpoint, Interpolation};
+
+    const START: TimestampSec = 1_600_000_000;
+
+    fn linear(start: TimestampSec, finish: TimestampSec, total_balance: Balance) -> Schedule {
+        Schedule::Checkpoints(vec![
+            Checkpoint {
+                timestamp: start,
+                balance: 0,
+                interpolation: Interpolation::Linear,
+            },
+            Checkpoint {
+                timestamp: finish,
+                balance: total_balance,
+                interpolation: Interpolation::Linear,
+            },
+        ])
+    }
+
+    fn terminable_lockup() -> Lockup {
+        let mut lockup = Lockup::new_unlocked("alice.near".parse().unwrap(), 1_000);
+        lockup.schedule = linear(START, START + 1_000, 1_000);
+        lockup.termination_config = Some(TerminationConfig {
+            beneficiary_id: "owner.near".parse().unwrap(),
+            vesting_schedule: VestingConditions::Schedule(linear(START, START + 500, 1_000)),
+            terminator_id: None,
+        });
+        lockup
+    }
+
+    #[test]
+    fn terminate_paused_lockup_with_scheduled_termination() {
+        let mut lockup = terminable_lockup();
+        let termination_timestamp = START + 600;
+        lockup.pending_termination = Some(PendingTermination {
+            termination_timestamp,
+            executable_at: START + 900,
+        });
+
+        lockup.pause(START + 200);
+        let (unvested_balance, beneficiary_id) = lockup.terminate(None, termination_timestamp, START + 900);
+
+        // the vesting is frozen during the pause, which ends at the termination timestamp
+        assert_eq!(unvested_balance, 600);
+        assert_eq!(beneficiary_id.as_str(), "owner.near");
+        assert_eq!(lockup.paused_at, None);
+        assert_eq!(lockup.pending_termination, None);
+        lockup.schedule.assert_valid(400);
+        assert_eq!(lockup.unlocked_balance(termination_timestamp), 200);
+        assert_eq!(lockup.schedule.finish_timestamp(), START + 800);
+        assert_eq!(lockup.unlocked_balance(START + 900), 400);
+    }
+
+    #[test]
+    fn resume_delays_both_schedules() {
+        let mut lockup = terminable_lockup();
+
+        lockup.pause(START + 200);
+        assert_eq!(lockup.resume(None, START + 300), 100);
+
+        assert_eq!(lockup.paused_at, None);
+        assert_eq!(lockup.unlocked_balance(START + 300), 200);
+        assert_eq!(lockup.unlocked_balance(START + 600), 500);
+        assert_eq!(lockup.schedule.finish_timestamp(), START + 1_100);
+
+        // the vesting is delayed as well, so nothing more is vested during the pause
+        let (unvested_balance, _) = lockup.terminate(None, START + 300, START + 300);
+        assert_eq!(unvested_balance, 600);
+    }
+
+    #[test]
+    fn reduce_scales_the_rest_of_schedules() {
+        let mut lockup = terminable_lockup();
+
+        let (reduced_balance, beneficiary_id) = lockup.reduce(LockupReduction::Percent(50), None, START + 250);
+
+        assert_eq!(reduced_balance, 250);
+        assert_eq!(beneficiary_id.as_str(), "owner.near");
+        lockup.schedule.assert_valid(750);
+        assert_eq!(lockup.unlocked_balance(START + 250), 250);
+        assert_eq!(lockup.unlocked_balance(START + 625), 500);
+        assert_eq!(lockup.unlocked_balance(START + 1_000), 750);
+        let Some(VestingConditions::Schedule(vesting_schedule)) = lockup
+            .termination_config
+            .as_ref()
+            .map(|config| &config.vesting_schedule)
+        else {
+            panic!("The vesting schedule is kept");
+        };
+        assert_eq!(vesting_schedule.unlocked_balance(START + 250), 500);
+        assert_eq!(vesting_schedule.unlocked_balance(START + 375), 625);
+        assert_eq!(vesting_schedule.unlocked_balance(START + 500), 750);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reduction exceeds the unvested balance")]
+    fn reduce_more_than_unvested() {
+        let mut lockup = terminable_lockup();
+
+        lockup.reduce(LockupReduction::Amount(501.into()), None, START + 250);
     }
 }
@@ -1,9 +1,68 @@
-use near_sdk::{AccountId, PromiseOrValue};
+use near_sdk::{json_types::Base58CryptoHash, near, AccountId, PromiseOrValue};
 use nitka::make_integration_version;
 
+use crate::TimestampSec;
+
 #[make_integration_version]
 pub trait UpdateApi {
     #[update]
     fn update_contract(&mut self) -> PromiseOrValue<()>;
+
+    /// Kept for compatibility, proposes the manager the same way `propose_manager` does.
     fn set_multisig(&mut self, multisig: AccountId);
 }
+
+/// The manager is changed in two steps, so a mistyped account can't take over the updates.
+#[make_integration_version]
+pub trait ManagerApi {
+    /// Proposes the account that becomes the manager once it accepts, `None` withdraws the proposal.
+    /// Can be called only by the manager.
+    fn propose_manager(&mut self, manager: Option<AccountId>);
+
+    /// Makes the predecessor the manager, can be called only by the proposed account.
+    fn accept_manager(&mut self);
+
+    fn get_manager(&self) -> AccountId;
+
+    fn get_proposed_manager(&self) -> Option<AccountId>;
+}
+
+/// A code update that can be applied with `update_contract` since `activation_timestamp`.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StagedUpdate {
+    /// The SHA-256 hash of the wasm code
+    pub code_hash: Base58CryptoHash,
+    pub activation_timestamp: TimestampSec,
+}
+
+/// Once the update delay is set, the code can only be updated with a staged update after the delay is over,
+/// so holders can see a coming update in advance.
+#[make_integration_version]
+pub trait UpdateTimelockApi {
+    /// Stages the code with the given hash, replacing a previously staged one, the delay starts over.
+    /// Can be called by the manager or an updater, every call emits an event.
+    fn stage_update(&mut self, code_hash: Base58CryptoHash);
+
+    /// Can be called by the manager or an updater.
+    fn cancel_update(&mut self);
+
+    /// Can be called only by the manager. The delay can only be increased,
+    /// lowering it requires a code update, which is a subject to the current delay.
+    fn set_update_delay(&mut self, update_delay: TimestampSec);
+
+    fn get_staged_update(&self) -> Option<StagedUpdate>;
+
+    fn get_update_delay(&self) -> TimestampSec;
+}
+
+/// Lockups and drafts of an older state layout are moved by pages after `migrate`,
+/// the lockups and drafts can't be used until they are all moved.
+#[make_integration_version]
+pub trait MigrationApi {
+    /// Moves up to `limit` entries, returns `true` once the state is fully migrated.
+    /// Can be called by the manager or an updater.
+    fn migrate_state(&mut self, limit: u32) -> bool;
+
+    fn is_state_migrated(&self) -> bool;
+}
@@ -3,16 +3,20 @@ use nitka::make_integration_version;
 
 use crate::{
     draft::{DraftGroupIndex, DraftGroupView, DraftIndex, DraftView},
-    lockup::{LockupIndex, LockupView},
+    lockup::{ClaimRecord, LockupIndex, LockupToken, LockupView},
+    pause::PausableOperation,
+    preset::SchedulePreset,
+    role::Role,
     schedule::Schedule,
-    WrappedBalance,
+    TimestampSec, TokenAccountId, WrappedBalance,
 };
 
 #[make_integration_version]
 pub trait LockupViewApi {
     fn get_token_account_id(&self) -> AccountId;
 
-    fn get_account_lockups(&self, account_id: AccountId) -> Vec<(LockupIndex, LockupView)>;
+    /// Returns lockups of the account, only of the given token if it's specified.
+    fn get_account_lockups(&self, account_id: AccountId, token: Option<LockupToken>) -> Vec<(LockupIndex, LockupView)>;
 
     fn get_lockup(&self, index: LockupIndex) -> Option<LockupView>;
     fn get_lockups(&self, indices: Vec<LockupIndex>) -> Vec<(LockupIndex, LockupView)>;
@@ -25,10 +29,34 @@ pub trait LockupViewApi {
         limit: Option<LockupIndex>,
     ) -> Vec<(LockupIndex, LockupView)>;
 
+    fn get_pending_lockup_transfer(&self, index: LockupIndex) -> Option<AccountId>;
+
+    /// Returns the latest claims of the lockup, from the oldest to the newest.
+    fn get_claim_history(&self, index: LockupIndex, from_index: Option<u32>, limit: Option<u32>) -> Vec<ClaimRecord>;
+
+    fn get_claim_history_limit(&self) -> u32;
+
+    /// Returns the lockup creators.
     fn get_deposit_whitelist(&self) -> Vec<AccountId>;
 
+    /// Returns the draft operators.
     fn get_draft_operators_whitelist(&self) -> Vec<AccountId>;
 
+    fn get_role_members(&self, role: Role) -> Vec<AccountId>;
+
+    fn has_role(&self, account_id: AccountId, role: Role) -> bool;
+
+    /// Returns the relayers.
+    fn get_relayers_whitelist(&self) -> Vec<AccountId>;
+
+    fn get_max_relayer_fee_bps(&self) -> u16;
+
+    fn get_token_whitelist(&self) -> Vec<TokenAccountId>;
+
+    fn get_termination_grace_period(&self) -> TimestampSec;
+
+    fn get_paused_operations(&self) -> Vec<PausableOperation>;
+
     fn hash_schedule(&self, schedule: Schedule) -> Base58CryptoHash;
 
     fn validate_schedule(
@@ -38,6 +66,8 @@ pub trait LockupViewApi {
         termination_schedule: Option<Schedule>,
     );
 
+    fn expand_schedule_preset(&self, preset: SchedulePreset, total_balance: WrappedBalance) -> Schedule;
+
     fn get_next_draft_group_id(&self) -> DraftGroupIndex;
 
     fn get_next_draft_id(&self) -> DraftGroupIndex;